@@ -0,0 +1,14 @@
+#version 330 core
+layout (location = 0) in vec4 vertex;
+layout (location = 1) in vec4 instance;        // xy centre, z size
+layout (location = 2) in vec4 instanceColor;
+out vec2 TexCoords;
+out vec4 ParticleColor;
+
+uniform mat4 projection;
+
+void main() {
+    TexCoords = vec2(vertex.z, 1.0 - vertex.w);
+    ParticleColor = instanceColor;
+    gl_Position = projection * vec4((vertex.xy - 0.5) * instance.z + instance.xy, 0.0, 1.0);
+}
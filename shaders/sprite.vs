@@ -4,8 +4,9 @@ out vec2 TexCoords;
 
 uniform mat4 model;
 uniform mat4 projection;
+uniform vec4 textureRegion;
 
 void main() {
-    TexCoords = vec2(vertex.z, 1.0 - vertex.w);
+    TexCoords = textureRegion.xy + vec2(vertex.z, 1.0 - vertex.w) * textureRegion.zw;
     gl_Position = projection *  model *   vec4(vertex.xy, 0.0, 1.0); 
 }
@@ -1,208 +1,1257 @@
 
 use systems::resource_manager::ResourceManager;
+use systems::audio_manager::AudioManager;
+use systems::asset_locator::user_file;
+use utilities::renderer::Renderer;
 use utilities::sprite_renderer::SpriteRenderer;
 use utilities::texture::Texture;
 use utilities::game_level::GameLevel;
+use utilities::bindings_serialization::{read_bindings_from_file, write_bindings_to_file};
+use utilities::manifest_serialization::{LevelList, Manifest, MANIFEST_FILE, read_manifest_from_file};
+use utilities::sound::open_stream_from_file;
+use utilities::power_up_serialization::read_power_ups_from_file;
+use utilities::random::Random;
+use utilities::post_processor::{PostProcessor, Effect};
+use utilities::text_renderer::{TextRenderer, TextStyle, TextAlignment};
+use utilities::canvas::{Canvas, ScaleMode};
 use objects::player::Player;
 use objects::ball::BallObject;
-use systems::input_manager::{Input, parse_input};
+use objects::power_up::{PowerUp, PowerUpDefinition, PowerUpKind};
+use systems::input_manager::{ControlScheme, Input, InputAxis, InputFrame, InputState, KeyBindings, INPUTS, INPUT_COUNT, input_index};
+use systems::particle_manager::{ParticleManager, Emitter};
 
 use std::cell::RefCell;
 use std::ptr::null;
 use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use gl::types::GLfloat;
 use nalgebra::base::{Matrix4, Vector2, Vector3};
 use sdl2::event::Event;
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-#[derive(Clone)]
+    use systems::resource_manager::TextureStorage;
+    use utilities::canvas::{VIRTUAL_WIDTH, VIRTUAL_HEIGHT};
+    use utilities::manifest_serialization::read_manifest_from_string;
+    use utilities::power_up_serialization::read_power_ups_from_string;
+    use utilities::screenshot::{Screenshot, read_png_from_file, write_png_to_file};
+    use utilities::software_renderer::SoftwareRenderer;
+
+    use std::env;
+    use std::path::Path;
+
+    /// Image of `levels/one.lvl` as it starts, drawn by `draws_a_level_like_the_golden_image`.
+    const GOLDEN_LEVEL_IMAGE: &str = "tests/golden/level_one.png";
+    /// Set to rewrite the golden images from the software renderer, after checking they still look right.
+    const UPDATE_GOLDEN_VARIABLE: &str = "BREAKOUT_UPDATE_GOLDEN";
+
+    /// A game playing `level` with no power-ups dropping.
+    fn headless_game(level: &str) -> Game {
+        let resource_manager = Rc::new(RefCell::new(ResourceManager::new()));
+        let audio = Rc::new(RefCell::new(AudioManager::with_null()));
+        let level = GameLevel::from_string(level).expect("Should work");
+        let mut game = Game::with_levels(&resource_manager, &audio, vec![level], Vec::new());
+        game.start_level(0);
+        game
+    }
+
+    /// Plays until `seconds` have passed, launching the ball whenever it is stuck and placing the
+    /// paddle's centre wherever `paddle` says, given the ball's centre.
+    fn play<F : Fn(GLfloat) -> GLfloat>(game: &mut Game, seconds: f32, paddle: F) {
+        let dt = 1.0 / 120.0;
+        let mut time = 0.0;
+        while time < seconds && game.state == GameState::GAME_ACTIVE {
+            let mut input = InputFrame::new();
+            input.pressed[input_index(Input::LAUNCH)] = game.ball.as_ref().map(|ball| ball.is_stuck()).unwrap_or(false);
+            game.replay_input(dt, input);
+            game.update(dt);
+
+            let ball = game.ball.as_ref().expect("Should work");
+            if !ball.is_stuck() {
+                let x = paddle(ball.center().x);
+                game.player.as_mut().expect("Should work").move_to(x);
+            }
+            time += dt;
+        }
+    }
+
+    /// Keeps the paddle at whichever end of the screen is further from the ball.
+    fn dodge(ball: GLfloat) -> GLfloat {
+        if ball < VIRTUAL_WIDTH / 2.0 { VIRTUAL_WIDTH } else { 0.0 }
+    }
+
+    /// Keeps the paddle under the ball, off centre by an amount that varies along the screen so the
+    /// ball does not settle into bouncing along the same path forever.
+    fn follow(ball: GLfloat) -> GLfloat {
+        ball + ::objects::player::PLAYER_SIZE_X * 0.4 * (ball / 10.0).sin()
+    }
+
+    /// A game at the start of `levels/one.lvl`, with the textures and font it draws loaded on the CPU.
+    fn drawable_game() -> Game {
+        // lossless images only, so they decode to the same pixels with any image library
+        let texture = |name: &str, file: &str, alpha: bool|
+            format!("[[texture]]\nname = \"{}\"\nfile = \"{}\"\nalpha = {}\n", name, file, alpha);
+        let manifest = read_manifest_from_string(&[
+            texture("background", "textures/block_solid.png", false),
+            texture("block", "textures/block.png", false),
+            texture("block_solid", "textures/block_solid.png", false),
+            texture("face", "textures/awesomeface.png", true),
+            texture("paddle", "textures/paddle.png", true),
+            "[[font]]\nname = \"default\"\nfile = \"fonts/dejavu_sans_bold.fnt\"\n".to_string(),
+            "[[level]]\nfile = \"levels/one.lvl\"\n".to_string(),
+        ].concat()).expect("Should work");
+
+        let resource_manager = Rc::new(RefCell::new(ResourceManager::new()));
+        resource_manager.borrow_mut()
+            .with_texture_storage(TextureStorage::CPU)
+            .locator_mut().with_first_directory(env!("CARGO_MANIFEST_DIR"));
+        resource_manager.borrow_mut().load_manifest(&manifest).expect("Should work");
+
+        let audio = Rc::new(RefCell::new(AudioManager::with_null()));
+        let level = GameLevel::from_string(include_str!("../levels/one.lvl")).expect("Should work");
+        let mut game = Game::with_levels(&resource_manager, &audio, vec![level], Vec::new());
+        let font = resource_manager.borrow().get_font("default").expect("Should work");
+        game.text = Some(TextRenderer::new(&font));
+        game.start_level(0);
+        game
+    }
+
+    /// Drops the alpha channel, which only matters for blending and is not kept by every framebuffer.
+    fn opaque(screenshot: &Screenshot) -> Screenshot {
+        let pixels = screenshot.pixels().chunks(4)
+            .flat_map(|pixel| vec![pixel[0], pixel[1], pixel[2], 255])
+            .collect();
+        Screenshot::new(screenshot.width(), screenshot.height(), pixels).expect("Should work")
+    }
+
+    #[test]
+    fn draws_a_level_like_the_golden_image() {
+        let mut game = drawable_game();
+        let mut renderer = SoftwareRenderer::new(VIRTUAL_WIDTH as u32, VIRTUAL_HEIGHT as u32);
+        renderer.set_projection(&game.projection);
+        game.render_with(&mut renderer, 0.0);
+        let drawn = opaque(&Screenshot::from_software(&renderer));
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_LEVEL_IMAGE);
+        if env::var_os(UPDATE_GOLDEN_VARIABLE).is_some() {
+            write_png_to_file(&path, &drawn).expect("Should work");
+        }
+
+        let golden = read_png_from_file(&path).expect("Should work");
+        // allow for rounding differences between platforms
+        let differences = drawn.count_differences(&golden, 2).expect("Should work");
+        assert!(differences < (drawn.width() * drawn.height() / 1000) as usize, "{} pixels differ", differences);
+    }
+
+    #[test]
+    #[ignore] // needs a display and OpenGL 3.3 - run with `cargo test -- --ignored`
+    fn draws_a_level_the_same_with_opengl_and_on_the_cpu() {
+        let sdl = ::sdl2::init().expect("Should work");
+        let video = sdl.video().expect("Should work");
+        video.gl_attr().set_context_profile(::sdl2::video::GLProfile::Core);
+        video.gl_attr().set_context_version(3, 3);
+        let window = video.window("golden", VIRTUAL_WIDTH as u32, VIRTUAL_HEIGHT as u32)
+            .opengl()
+            .hidden()
+            .build()
+            .expect("Should work");
+        let _context = window.gl_create_context().expect("Should work");
+        ::gl::load_with(|name| video.gl_get_proc_address(name) as *const ::std::os::raw::c_void);
+        unsafe {
+            ::gl::Enable(::gl::CULL_FACE);
+            ::gl::Enable(::gl::BLEND);
+            ::gl::BlendFunc(::gl::SRC_ALPHA, ::gl::ONE_MINUS_SRC_ALPHA);
+        }
+
+        let resource_manager = Rc::new(RefCell::new(ResourceManager::new()));
+        resource_manager.borrow_mut().with_texture_storage(TextureStorage::GPU_AND_CPU);
+        let audio = Rc::new(RefCell::new(AudioManager::with_null()));
+        let mut game = Game::new(&resource_manager, &audio);
+        game.init();
+        game.set_window_size(window.size(), window.drawable_size());
+        game.start_level(0);
+
+        let (width, height) = window.drawable_size();
+        unsafe {
+            ::gl::Clear(::gl::COLOR_BUFFER_BIT);
+        }
+        game.render(0.0);
+        let opengl = opaque(&Screenshot::from_framebuffer(width, height));
+
+        let mut renderer = SoftwareRenderer::new(width, height);
+        renderer.set_projection(&game.projection);
+        game.render_with(&mut renderer, 0.0);
+        let software = opaque(&Screenshot::from_software(&renderer));
+
+        // edges are rasterized and multisampled differently, but the bulk of the image must agree
+        let differences = opengl.count_differences(&software, 16).expect("Should work");
+        assert!(differences < (width * height / 50) as usize, "{} pixels differ", differences);
+    }
+
+    #[test]
+    fn interpolates_the_paddle_and_the_ball_it_carries_together() {
+        let mut game = headless_game("1:4\n2,\n0,\n0,\n0,\n");
+        let mut input = InputFrame::new();
+        input.horizontal = 1.0;
+        game.replay_input(1.0 / 120.0, input);
+        game.update(1.0 / 120.0);
+
+        let paddle = game.player.as_ref().expect("Should work").as_ref();
+        let ball = game.ball.as_ref().expect("Should work").as_ref();
+        assert!(paddle.interpolated_position(0.0).x < paddle.position().x);
+        assert!(((ball.interpolated_position(0.5).x - paddle.interpolated_position(0.5).x)
+            - (ball.position().x - paddle.position().x)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn draws_a_paused_game_standing_still() {
+        let mut game = drawable_game();
+        let mut renderer = SoftwareRenderer::new(VIRTUAL_WIDTH as u32, VIRTUAL_HEIGHT as u32);
+        renderer.set_projection(&game.projection);
+        let mut draw = |game: &mut Game, alpha: f32| {
+            game.render_with(&mut renderer, alpha);
+            Screenshot::from_software(&renderer)
+        };
+
+        let mut input = InputFrame::new();
+        input.pressed[input_index(Input::LAUNCH)] = true;
+        game.replay_input(1.0 / 120.0, input);
+        game.update(1.0 / 120.0);
+        assert!(draw(&mut game, 0.0).count_differences(&draw(&mut game, 1.0), 0).expect("Should work") > 0);
+
+        let mut input = InputFrame::new();
+        input.pressed[input_index(Input::PAUSE)] = true;
+        game.replay_input(1.0 / 120.0, input);
+        game.update(1.0 / 120.0);
+        assert!(game.state == GameState::GAME_PAUSED);
+        assert_eq!(draw(&mut game, 0.0).count_differences(&draw(&mut game, 1.0), 0).expect("Should work"), 0);
+    }
+
+    #[test]
+    fn resizing_the_window_leaves_sparse_bricks_in_their_cells() {
+        let mut game = headless_game("2:3\n0,2,\n0,0,\n1,0,\n");
+        game.set_window_size((1280, 720), (2560, 1440));
+
+        // only the letterbox viewport changes, bricks stay put in canvas units
+        let bricks = game.levels[0].bricks_mut();
+        assert_eq!(bricks.len(), 2);
+        assert_eq!(*bricks[0].position(), Vector2::new(VIRTUAL_WIDTH / 2.0, VIRTUAL_HEIGHT * 2.0 / 3.0));
+        assert_eq!(*bricks[1].position(), Vector2::new(0.0, 0.0));
+        assert_eq!(*bricks[1].size(), Vector2::new(VIRTUAL_WIDTH / 2.0, VIRTUAL_HEIGHT / 3.0));
+    }
+
+    #[test]
+    fn reports_every_level_that_fails_to_load() {
+        let manifest = read_manifest_from_string(
+            "[[level]]\nfile = \"levels/missing.lvl\"\n\
+             [[level]]\nfile = \"levels/one.lvl\"\n\
+             [[level]]\nfile = \"levels/also_missing.lvl\"\n").expect("Should work");
+        let game = headless_game("1:1\n2,\n");
+
+        let e = game.load_simulation(&manifest).err().expect("Should work");
+        assert!(e.contains("levels/missing.lvl") && e.contains("levels/also_missing.lvl"));
+        assert!(!e.contains("levels/one.lvl"));
+    }
+
+    #[test]
+    fn clearing_the_last_level_wins() {
+        let mut game = headless_game("1:4\n2,\n0,\n0,\n0,\n");
+        play(&mut game, 10.0, follow);
+        assert!(game.state == GameState::GAME_WIN);
+    }
+
+    #[test]
+    fn losing_every_ball_ends_the_game() {
+        // the breakable brick in the top row is shielded by the solid one, so the level can never be cleared
+        let mut game = headless_game("1:4\n2,\n1,\n0,\n0,\n");
+        play(&mut game, 30.0, dodge);
+        assert!(game.state == GameState::GAME_OVER);
+        assert_eq!(game.lives, 0);
+    }
+
+    #[test]
+    fn losing_the_last_ball_as_the_last_brick_breaks_ends_the_game() {
+        let mut game = headless_game("1:4\n2,\n0,\n0,\n0,\n");
+        game.lives = 1;
+        game.levels[0].bricks_mut()[0].destroy();
+        {
+            let ball = game.ball.as_mut().expect("Should work");
+            ball.launch();
+            ball.as_mut().position_mut().y = -100.0;
+        }
+
+        game.update(1.0 / 120.0);
+        assert!(game.state == GameState::GAME_OVER);
+        assert_eq!(game.lives, 0);
+    }
+
+    #[test]
+    fn plays_a_shipped_level_without_losing_the_ball() {
+        let resource_manager = Rc::new(RefCell::new(ResourceManager::new()));
+        let audio = Rc::new(RefCell::new(AudioManager::with_null()));
+        let level = GameLevel::from_string(include_str!("../levels/one.lvl")).expect("Should work");
+        let power_ups = read_power_ups_from_string(include_str!("../config/powerups.cfg")).expect("Should work");
+        let mut game = Game::with_levels(&resource_manager, &audio, vec![level], power_ups);
+        game.set_seed(1);
+        game.start_level(0);
+
+        let bricks_left = |game: &mut Game| game.levels[0].bricks_mut().iter()
+            .filter(|brick| !brick.is_solid() && !brick.is_destroyed())
+            .count();
+        let bricks = bricks_left(&mut game);
+
+        play(&mut game, 60.0, follow);
+        assert!(game.state == GameState::GAME_ACTIVE);
+        assert!(bricks_left(&mut game) < bricks);
+        assert_eq!(game.lives, INITIAL_LIVES);
+    }
+}
+
+
+pub const INITIAL_LIVES: u32 = 3;
+pub const MAX_PARTICLES: usize = 4096;
+pub const BALL_TRAIL_RATE: GLfloat = 200.0;
+pub const BALL_TRAIL_LIFE: GLfloat = 0.6;
+pub const DEBRIS_PARTICLES: usize = 40;
+pub const SOLID_HIT_SHAKE_TIME: GLfloat = 0.05;
+pub const PAD_SIZE_INCREASE: GLfloat = 50.0;
+pub const SPEED_INCREASE: GLfloat = 1.2;
+pub const EFFECT_VOLUME: GLfloat = 0.8;
+pub const MUSIC_VOLUME: GLfloat = 0.5;
+pub const MUSIC_CROSSFADE_TIME: GLfloat = 1.5;
+pub const MUSIC_DUCK_LEVEL: GLfloat = 0.3;
+pub const MUSIC_DUCK_TIME: GLfloat = 1.0;
+pub const BINDINGS_FILE: &str = "config/bindings.cfg";
+pub const POWER_UPS_FILE: &str = "config/powerups.cfg";
+
+#[derive(Clone, PartialEq)]
 pub enum GameState {
     GAME_ACTIVE,
     GAME_MENU,
     GAME_WIN,
+    GAME_OVER,
+    GAME_PAUSED,
+    GAME_OPTIONS,
 }
 
 pub struct Game {
     state: GameState,
-    width: u32,
-    height: u32,
     resource_manager: Rc<RefCell<ResourceManager>>,
+    audio: Rc<RefCell<AudioManager>>,
+    level_list: Option<LevelList>,
     levels: Vec<GameLevel>,
     current_level: Option<usize>,
-    renderer: Option<SpriteRenderer>,
+    renderer: Option<Box<Renderer>>,
+    text: Option<TextRenderer>,
+    particles: Option<ParticleManager>,
+    post_processor: Option<PostProcessor>,
+    ball_trail: Emitter,
     player: Option<Player>,
-    ball: Option<BallObject>
+    ball: Option<BallObject>,
+    input: InputState,
+    selected_binding: usize,   // row highlighted on the options screen - an action, or the control scheme after them
+    is_rebinding: bool,        // waiting for a key to add to or remove from the selected action
+    is_screenshot_requested: bool,
+    power_up_definitions: Vec<PowerUpDefinition>,
+    power_ups: Vec<PowerUp>,
+    seed: u64,               // seed of `random`, recorded so replays make the same choices
+    random: Random,
+    lives: u32,
+    music: Option<String>,   // track currently streaming
+    projection: Matrix4<GLfloat>,
+    canvas: Canvas,          // fixed size area everything is placed in, scaled to the window
+    time: GLfloat
 }
 
 static mut RENDERER: Option<SpriteRenderer> = None;
 
 impl Game {
-    pub fn new(resource_manager: &Rc<RefCell<ResourceManager>>) -> Self {
+    pub fn new(resource_manager: &Rc<RefCell<ResourceManager>>, audio: &Rc<RefCell<AudioManager>>) -> Self {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or(0);
         Game {
-            state: GameState::GAME_ACTIVE,
-            width: 700,
-            height: 900,
+            state: GameState::GAME_MENU,
             resource_manager: resource_manager.clone(),
+            audio: audio.clone(),
+            level_list: None,
             levels: Vec::new(),
             current_level: None,
             renderer: None,
+            text: None,
+            particles: None,
+            post_processor: None,
+            ball_trail: Emitter::new(BALL_TRAIL_RATE, BALL_TRAIL_LIFE),
             player: None,
-            ball: None
+            ball: None,
+            input: InputState::new(KeyBindings::new()),
+            selected_binding: 0,
+            is_rebinding: false,
+            is_screenshot_requested: false,
+            power_up_definitions: Vec::new(),
+            power_ups: Vec::new(),
+            seed: seed,
+            random: Random::new(seed),
+            lives: INITIAL_LIVES,
+            music: None,
+            projection: Matrix4::identity(),
+            canvas: Canvas::new(),
+            time: 0.0
         }
     }
+
+    /// A game playing `levels`, with just its simulation set up.
+    ///
+    /// Nothing is read from disk and no graphics context is needed, so the game can be updated
+    /// headlessly, such as in tests.
+    pub fn with_levels(resource_manager: &Rc<RefCell<ResourceManager>>,
+                       audio: &Rc<RefCell<AudioManager>>,
+                       levels: Vec<GameLevel>,
+                       power_up_definitions: Vec<PowerUpDefinition>) -> Self {
+        let mut game = Game::new(resource_manager, audio);
+        game.init_objects(levels, power_up_definitions);
+        game
+    }
+
     pub fn init(&mut self) { // Loading resources
-        let shader = self.resource_manager.borrow_mut().load_shader(
-            "/home/gopiandcode/Documents/Rust/gui-base/shaders/sprite.vs",
-            "/home/gopiandcode/Documents/Rust/gui-base/shaders/sprite.frag",
-            "sprite",
-        ).expect("sprite shader could not be loaded");
+        let manifest = self.read_manifest();
+
+        // every broken asset, level and power-up is reported together, rather than just the first
+        let mut errors = Vec::new();
+        if let Err(e) = self.resource_manager.borrow_mut().load_manifest(&manifest) {
+            errors.push(e);
+        }
+        let (levels, power_up_definitions) = match self.load_simulation(&manifest) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                errors.push(e);
+                (Vec::new(), Vec::new())
+            }
+        };
+        if !errors.is_empty() {
+            panic!("Could not load the game:\n{}", errors.join("\n"));
+        }
+
+        self.init_graphics();
+        self.level_list = Some(manifest.levels);
+        self.init_objects(levels, power_up_definitions);
+
+        let bindings = self.resource_manager.borrow().locate(BINDINGS_FILE)
+            .and_then(|file| read_bindings_from_file(&file));
+        match bindings {
+            Ok(bindings) => *self.input.bindings_mut() = bindings,
+            Err(e) => println!("| ERROR::GAME: Could not read key bindings, using the defaults - {}", e),
+        }
+    }
+
+    /// Reads the list of assets and levels to load.
+    fn read_manifest(&self) -> Manifest {
+        self.resource_manager.borrow().locate(MANIFEST_FILE)
+            .and_then(|file| read_manifest_from_file(&file))
+            .expect("Could not read asset manifest")
+    }
+
+    /// Sets up everything used for drawing, once the assets are loaded.
+    fn init_graphics(&mut self) {
+        let shader = self.resource_manager.borrow().get_shader("sprite").expect("sprite shader could not be loaded");
+        self.renderer = Some(Box::new(SpriteRenderer::new(&shader)));
+
+        let particle_shader = self.resource_manager.borrow().get_shader("particle").expect("particle shader could not be loaded");
         {
-            let mut manager = self.resource_manager.borrow_mut();
-            for (filename, is_alpha, reference) in &[
-                ("textures/background.jpg", false, "background"),
-                ("textures/block.png", false, "block"),
-                ("textures/block_solid.png", false, "block_solid"),
-                ("/home/gopiandcode/Documents/Rust/gui-base/textures/awesomeface.png", true, "face"),
-                ("textures/paddle.png", true, "paddle")
-            ] {
-                manager.load_texture(filename, *is_alpha, reference)
-                    .expect(&format!("Could not find file {}", filename));
+            let texture = self.resource_manager.borrow().get_texture("particle").expect("Could not load particle texture");
+            self.particles = Some(ParticleManager::new(&particle_shader, &texture, MAX_PARTICLES));
+
+            let radius = ::objects::ball::BALL_RADIUS;
+            self.ball_trail
+                .with_offset(Vector2::new(radius, radius))
+                .with_size(radius * 1.2)
+                .with_spread(radius * 0.25)
+                .with_inherit_velocity(-0.1)
+                .with_color(Vector3::new(1.0, 0.8, 0.5));
+        }
+
+        {
+            let post_shader = self.resource_manager.borrow().get_shader("post_processing")
+                .expect("post processing shader could not be loaded");
+            // drawn at the viewport's size in pixels, and resized along with it
+            let viewport = *self.canvas.viewport();
+            let post_processor = PostProcessor::new(&post_shader, viewport.width, viewport.height)
+                .expect("post processor could not be created");
+            self.post_processor = Some(post_processor);
+        }
+
+        {
+            let font = self.resource_manager.borrow().get_font("default")
+                .expect("default font could not be loaded");
+            self.text = Some(TextRenderer::new(&font));
+        }
+
+
+        unsafe {
+            shader.borrow_mut().enable();
+            shader.borrow_mut().setInt("image", 0);
+            particle_shader.borrow_mut().enable();
+            particle_shader.borrow_mut().setInt("sprite", 0);
+        }
+        self.update_projection();
+    }
+
+    /// Rebuilds the projection from the canvas and hands it to the shaders, once they are loaded.
+    fn update_projection(&mut self) {
+        let projection = self.canvas.projection();
+        self.projection = projection;
+        self.input.set_view(&projection, self.canvas.viewport());
+
+        for name in ["sprite", "particle"].iter() {
+            if let Some(shader) = self.resource_manager.borrow().get_shader(name) {
+                unsafe {
+                    let mut _shader = shader.borrow_mut();
+                    _shader.enable();
+                    _shader.setMatrix4("projection", &projection);
+                }
             }
         }
+    }
 
-        let width = self.width;
-        let height = self.height;
+    /// Loads the manifest's levels and the power-ups from disk, describing every one that failed.
+    fn load_simulation(&self, manifest: &Manifest) -> Result<(Vec<GameLevel>, Vec<PowerUpDefinition>), String> {
+        let locate = |file: &str| self.resource_manager.borrow().locate(file);
 
-        for file_name in &["levels/one.lvl", "levels/two.lvl", "levels/three.lvl", "levels/four.lvl"] {
-            let mut level = GameLevel::from_file(&self.resource_manager, file_name, width, height)
-                .expect(&format!("Could not open file {}", file_name));
+        let mut levels = Vec::new();
+        let mut errors = Vec::new();
+        for entry in manifest.levels.levels.iter() {
+            match locate(&entry.file).and_then(|file| GameLevel::from_file(&file)) {
+                Ok(level) => levels.push(level),
+                Err(e) => errors.push(format!("level {} - {}", entry.file, e)),
+            }
+        }
+
+        let power_up_definitions = match locate(POWER_UPS_FILE).and_then(|file| read_power_ups_from_file(&file)) {
+            Ok(definitions) => definitions,
+            Err(e) => {
+                errors.push(format!("power-ups {} - {}", POWER_UPS_FILE, e));
+                Vec::new()
+            }
+        };
 
-            self.levels.push(level);
+        if errors.is_empty() {
+            Ok((levels, power_up_definitions))
+        } else {
+            Err(errors.join("\n"))
         }
+    }
 
+    /// Takes the levels and power-ups to play with and places the paddle and ball.
+    fn init_objects(&mut self, levels: Vec<GameLevel>, power_up_definitions: Vec<PowerUpDefinition>) {
+        self.levels = levels;
+        self.power_up_definitions = power_up_definitions;
         self.current_level = Some(0);
-        self.renderer = Some(SpriteRenderer::new(&shader));
-
 
         // setup player
-        {
-            let _resource_manager = self.resource_manager.borrow();
-            let texture = _resource_manager.get_texture("paddle").expect("Could not load paddle texture");
-            self.player = Some(Player::new(Vector2::new((self.width as f32/2.0), 10.0), (width as f32, height as f32), &texture));
-        }
+        self.player = Some(Player::new(self.initial_player_position(), "paddle"));
 
         {
-            let _resource_manager = self.resource_manager.borrow();
-            let texture = _resource_manager.get_texture("face").expect("Could not load face texture");
             let position = Vector2::new(
-                (self.width as f32/2.0) +  ::objects::player::PLAYER_SIZE_X / 2.0 - ::objects::ball::BALL_RADIUS, 
+                self.canvas.width() / 2.0 +  ::objects::player::PLAYER_SIZE_X / 2.0 - ::objects::ball::BALL_RADIUS, 
                 ::objects::ball::BALL_RADIUS * 2.0);
             let radius = ::objects::ball::BALL_RADIUS;
             let velocity = Vector2::new(::objects::ball::BALL_VELOCITY_X, ::objects::ball::BALL_VELOCITY_Y);
 
             
-            self.ball = Some(BallObject::new(position, radius, velocity, &texture));
+            self.ball = Some(BallObject::new(position, radius, velocity, "face"));
+        }
+        self.reset_ball();
+        self.update_projection();
+    }
+
+
+    pub fn processInput(&mut self, dt: f32, events: &Vec<Event>) {
+        self.input.update(events);
+        self.handle_input(dt);
+    }
+
+    /// Processes a recorded frame of input in place of live events.
+    pub fn replay_input(&mut self, dt: f32, input: InputFrame) {
+        self.input.set_frame(input);
+        self.handle_input(dt);
+    }
+
+    /// The input processed this frame, for recording.
+    pub fn input_frame(&self) -> &InputFrame {
+        self.input.frame()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts the random number generator, so a recorded game can be reproduced.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.random = Random::new(seed);
+    }
+
+    pub fn control_scheme(&self) -> ControlScheme {
+        self.input.bindings().scheme()
+    }
+
+    pub fn set_control_scheme(&mut self, scheme: ControlScheme) {
+        self.input.bindings_mut().set_scheme(scheme);
+    }
+
+    fn handle_input(&mut self, dt: f32) {
+        // works on every screen, but not while a key is being picked for an action
+        if self.input.is_pressed(Input::SCREENSHOT) && !self.is_rebinding {
+            self.is_screenshot_requested = true;
         }
 
+        match self.state {
+            GameState::GAME_MENU => {
+                let count = self.levels.len();
+                let index = self.current_level.unwrap_or(0);
 
-        let projection = Matrix4::new_orthographic(
-            0.0,                    // left
-            self.width as GLfloat,  // right
-            0.0,                    // bottom
-            self.height as GLfloat, // top
-            -1.0,                   // znear
-            1.0,                    // zfar
-        );
+                if self.input.is_pressed(Input::ACTION0) {
+                    self.start_level(index);
+                } else if self.input.is_pressed(Input::BACK) {
+                    self.open_options();
+                } else if self.input.is_pressed(Input::UP) {
+                    // previous level, wrapping around to the last
+                    self.current_level = Some((index + count - 1) % count);
+                } else if self.input.is_pressed(Input::DOWN) {
+                    self.current_level = Some((index + 1) % count);
+                }
+            }
+            GameState::GAME_ACTIVE => {
+                if self.input.is_pressed(Input::PAUSE) || self.input.is_pressed(Input::BACK) {
+                    self.state = GameState::GAME_PAUSED;
+                    return;
+                }
 
+                // the paddle moves here rather than in `update`, so its step starts before the input
+                self.save_positions();
 
-        unsafe {
-            {
-                let mut _shader = shader.borrow_mut();
-                _shader.enable();
-                _shader.setInt("image", 0);
-                _shader.setMatrix4("projection", &projection);
+                let mut player : &mut Player = self.player.as_mut().expect("Game error -render called before player initialized");
+                let mut ball : &mut BallObject = self.ball.as_mut().expect("Game error render called before ball initialized");
+
+                // movement keeps going for as long as the key is held, independent of key repeat,
+                // and a controller stick gives proportional speed
+                let movement = self.input.axis(InputAxis::HORIZONTAL);
+                if movement != 0.0 {
+                    player.move_by(movement, dt);
+                }
+
+                match self.input.bindings().scheme() {
+                    ControlScheme::KEYBOARD => (),
+                    ControlScheme::MOUSE => {
+                        // the mouse is captured, so only its movement is meaningful
+                        let center = player.as_ref().position().x + player.as_ref().size().x / 2.0;
+                        player.move_to(center + self.input.mouse_motion());
+                    }
+                    ControlScheme::TOUCH => {
+                        if let Some(touch) = self.input.touch_position() {
+                            player.move_to(touch.x);
+                        }
+                    }
+                }
+
+                if self.input.is_pressed(Input::LAUNCH)
+                    || (self.input.bindings().scheme() != ControlScheme::KEYBOARD && self.input.is_pointer_pressed()) {
+                    // release the ball from the paddle
+                    ball.launch();
+                }
+            }
+            GameState::GAME_PAUSED => {
+                if self.input.is_pressed(Input::PAUSE) || self.input.is_pressed(Input::ACTION0) {
+                    self.state = GameState::GAME_ACTIVE;
+                } else if self.input.is_pressed(Input::BACK) {
+                    self.open_menu();
+                }
+            }
+            GameState::GAME_OPTIONS => self.process_options_input(),
+            GameState::GAME_OVER | GameState::GAME_WIN => {
+                if self.input.is_pressed(Input::ACTION0) {
+                    self.open_menu();
+                }
             }
         }
     }
 
+    /// Lets the player pick an action and then press a key to add or remove it from that action,
+    /// or cycle through the control schemes.
+    fn process_options_input(&mut self) {
+        let rows = INPUT_COUNT + 1;
 
-    pub fn processInput(&mut self, dt: f32, events: &Vec<Event>) {
-        let mut player : &mut Player = self.player.as_mut().expect("Game error -render called before player initialized");
-        match &self.state {
-            ref GAME_ACTIVE => {
-                for event in events { println!("{:?}", event)}
+        if self.is_rebinding {
+            if let Some(&keycode) = self.input.pressed_keys().first() {
+                let input = INPUTS[self.selected_binding];
+                self.input.bindings_mut().toggle(input, keycode);
+                self.is_rebinding = false;
+            }
+        } else if self.input.is_pressed(Input::ACTION0) {
+            if self.selected_binding < INPUT_COUNT {
+                self.is_rebinding = true;
+            } else {
+                let scheme = self.input.bindings().scheme().next();
+                self.input.bindings_mut().set_scheme(scheme);
+            }
+        } else if self.input.is_pressed(Input::BACK) {
+            // saved for this user alone, leaving the shipped defaults untouched
+            let saved = user_file(BINDINGS_FILE)
+                .and_then(|file| write_bindings_to_file(&file, self.input.bindings()));
+            if let Err(e) = saved {
+                println!("| ERROR::GAME: Could not save key bindings - {}", e);
+            }
+            self.state = GameState::GAME_MENU;
+        } else if self.input.is_pressed(Input::UP) {
+            self.selected_binding = (self.selected_binding + rows - 1) % rows;
+        } else if self.input.is_pressed(Input::DOWN) {
+            self.selected_binding = (self.selected_binding + 1) % rows;
+        }
+    }
+
+    /// Returns true once after the screenshot key has been pressed, for the caller to capture the
+    /// next rendered frame.
+    pub fn take_screenshot_request(&mut self) -> bool {
+        let requested = self.is_screenshot_requested;
+        self.is_screenshot_requested = false;
+        requested
+    }
+
+    /// The mouse is captured while it is steering the paddle in play.
+    pub fn wants_relative_mouse(&self) -> bool {
+        self.state == GameState::GAME_ACTIVE && self.input.bindings().scheme() == ControlScheme::MOUSE
+    }
+
+    /// Fits the canvas to a resized window, keeping its aspect ratio with bars around it.
+    ///
+    /// `drawable` is the size of the window in pixels, which differs from `window` on HiDPI displays.
+    pub fn set_window_size(&mut self, window: (u32, u32), drawable: (u32, u32)) {
+        self.input.set_window_size(window.0, window.1);
+        self.canvas.fit(drawable.0, drawable.1);
+        self.input.set_view(&self.projection, self.canvas.viewport());
+
+        let viewport = *self.canvas.viewport();
+        if let Some(post_processor) = self.post_processor.as_mut() {
+            if let Err(e) = post_processor.resize(viewport.width, viewport.height) {
+                println!("{}", e);
+            }
+        }
+    }
+
+    /// Changes how the canvas is scaled up to the window, taking effect at the next `set_window_size`.
+    pub fn set_scale_mode(&mut self, scale_mode: ScaleMode) {
+        self.canvas.with_scale_mode(scale_mode);
+    }
+
+    /// Advances the simulation by one step, after `processInput` or `replay_input` has handled the
+    /// input for it.
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+        if let Some(post_processor) = self.post_processor.as_mut() {
+            post_processor.update(dt);
+        }
+        self.update_music();
 
-               for input in parse_input(events) {
-                   println!("Parsed: {:?}", input);
+        if self.state != GameState::GAME_ACTIVE {
+            return;
+        }
 
-                   match input {
-                       Input::LEFT => {
-                            // pass input to player
-                            player.move_left(dt);
-                       }
-                       Input::RIGHT => {
+        {
+            let player : &Player = self.player.as_ref().expect("Game error render called before player initialized");
+            let mut ball : &mut BallObject = self.ball.as_mut().expect("Game error render called before ball initialized");
+            ball.update(dt);
 
-                           // pass input to player
-                            player.move_right(dt);
-                       }
-                       Input::DOWN => {
+            // a stuck ball rides along with the paddle
+            ball.follow(player.as_ref());
 
-                           // pass input to player
-                       }
-                       Input::UP => {
+            // particles are only created along with the graphics
+            if let Some(particles) = self.particles.as_mut() {
+                particles.update(dt);
+                if !ball.is_stuck() {
+                    particles.emit(&mut self.ball_trail, ball.as_ref(), dt);
+                }
+            }
+        }
+
+        self.do_collisions();
+        self.update_power_ups(dt);
 
-                           // pass input to player
-                       }
-                       _ => ()
+        let ball_lost = self.ball.as_ref().map(|ball| ball.is_out_of_bounds()).unwrap_or(false);
+        if ball_lost {
+            self.lose_life();
+            if self.state != GameState::GAME_ACTIVE {
+                // bricks broken by the last ball do not count once the game is over
+                return;
+            }
+        }
 
-                   }
-               }
-           }
-            _ => return
+        let index = self.current_level.expect("Game error - No Current Level");
+        if self.levels[index].is_completed() {
+            self.advance_level();
         }
     }
 
-    pub fn update(&mut self, dt: f32) {
+    /// Moves on to the next level, or wins the game if the last level has been cleared.
+    fn advance_level(&mut self) {
+        let index = self.current_level.expect("Game error - No Current Level");
+        let next = index + 1;
 
-        let mut player : &mut Player = self.player.as_mut().expect("Game error render called before player initialized");
-        let mut ball : &mut BallObject = self.ball.as_mut().expect("Game error render called before ball initialized");
-        println!("ball_pos: {:?}", ball.update(dt, self.width, self.height));
+        if next < self.levels.len() {
+            self.levels[next].reset().expect("Game error - could not reset level");
+            self.current_level = Some(next);
+            self.reset_player_and_ball();
+        } else {
+            self.state = GameState::GAME_WIN;
+        }
+    }
+
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Starts a new game from the level at `index`, with every level restored to its original layout.
+    pub fn start_level(&mut self, index: usize) {
+        for level in self.levels.iter_mut() {
+            level.reset().expect("Game error - could not reset level");
+        }
+        self.current_level = Some(index);
+        self.lives = INITIAL_LIVES;
+        self.state = GameState::GAME_ACTIVE;
+        self.reset_player_and_ball();
+    }
+
+    /// Returns to the main menu with the first level selected.
+    pub fn open_menu(&mut self) {
+        for level in self.levels.iter_mut() {
+            level.reset().expect("Game error - could not reset level");
+        }
+        self.current_level = Some(0);
+        self.state = GameState::GAME_MENU;
+    }
+
+    /// Shows the key bindings, which are saved when the player backs out to the menu.
+    pub fn open_options(&mut self) {
+        self.selected_binding = 0;
+        self.is_rebinding = false;
+        self.state = GameState::GAME_OPTIONS;
+    }
+
+    fn initial_player_position(&self) -> Vector2<GLfloat> {
+        Vector2::new(self.canvas.width() / 2.0 - ::objects::player::PLAYER_SIZE_X / 2.0, 10.0)
+    }
+
+    fn reset_player_and_ball(&mut self) {
+        if let Some(particles) = self.particles.as_mut() {
+            particles.clear();
+        }
+        self.clear_power_ups();
+        let position = self.initial_player_position();
+        self.player.as_mut().expect("Game error player reset before player initialized").reset(position);
+        self.reset_ball();
+        self.save_positions();
+    }
+
+    /// Removes a life after the ball has been lost, ending the game once none remain.
+    fn lose_life(&mut self) {
+        self.lives = self.lives.saturating_sub(1);
+        let center = self.canvas.width() / 2.0;
+        self.play_sound("life_lost", center);
+        self.audio.borrow_mut().duck_music(MUSIC_DUCK_LEVEL, MUSIC_DUCK_TIME);
+
+        if self.lives == 0 {
+            self.state = GameState::GAME_OVER;
+        } else {
+            self.clear_power_ups();
+            self.reset_ball();
+        }
+    }
+
+    /// Places the ball back on top of the paddle, stuck until it is launched again.
+    fn reset_ball(&mut self) {
+        let player : &Player = self.player.as_ref().expect("Game error ball reset before player initialized");
+        let mut ball : &mut BallObject = self.ball.as_mut().expect("Game error ball reset before ball initialized");
+        let paddle = player.as_ref();
+
+        let position = Vector2::new(
+            paddle.position().x + paddle.size().x / 2.0 - ::objects::ball::BALL_RADIUS,
+            paddle.position().y + paddle.size().y);
+        let velocity = Vector2::new(::objects::ball::BALL_VELOCITY_X, ::objects::ball::BALL_VELOCITY_Y);
+
+        ball.reset(&position, &velocity);
+        // a teleported ball should not be drawn sliding to its new position
+        ball.as_mut().save_position();
+    }
+
+    /// Remembers where moving objects start this update, for drawing between updates.
+    fn save_positions(&mut self) {
+        if let Some(player) = self.player.as_mut() {
+            player.as_mut().save_position();
+        }
+        if let Some(ball) = self.ball.as_mut() {
+            ball.as_mut().save_position();
+        }
+        for power_up in self.power_ups.iter_mut() {
+            power_up.as_mut().save_position();
+        }
+    }
+
+    fn do_collisions(&mut self) {
+        let mut destroyed = Vec::new();
+        let mut sounds = Vec::new();
+        {
+            let mut ball : &mut BallObject = self.ball.as_mut().expect("Game error collisions checked before ball initialized");
+            let index = self.current_level.expect("Game error - No Current Level");
+            let level = &mut self.levels[index];
+
+            for brick in level.bricks_mut().iter_mut() {
+                if brick.is_destroyed() {
+                    continue;
+                }
+
+                if let Some(collision) = ball.check_collision(brick) {
+                    let x = brick.position().x + brick.size().x / 2.0;
+                    sounds.push((if brick.is_solid() { "solid" } else { "brick" }, x));
+
+                    if !brick.is_solid() {
+                        brick.destroy();
+                        destroyed.push(brick.position().clone());
+
+                        if let Some(particles) = self.particles.as_mut() {
+                            particles.burst(brick, DEBRIS_PARTICLES, 150.0, 1.0);
+                        }
+                    } else if let Some(post_processor) = self.post_processor.as_mut() {
+                        post_processor.enable_for(Effect::SHAKE, SOLID_HIT_SHAKE_TIME);
+                    }
+
+                    // a pass-through ball only bounces off solid bricks
+                    if brick.is_solid() || !ball.is_pass_through() {
+                        ball.resolve_collision(&collision);
+                    }
+                }
+            }
+
+            let player : &Player = self.player.as_ref().expect("Game error collisions checked before player initialized");
+            if !ball.is_stuck() && ball.check_collision(player.as_ref()).is_some() {
+                ball.bounce_off_paddle(player.as_ref());
+                sounds.push(("paddle", ball.center().x));
+            }
+        }
+
+        for (sound, x) in sounds {
+            self.play_sound(sound, x);
+        }
+
+        for position in destroyed {
+            self.spawn_power_ups(position);
+        }
+    }
+
+    /// Rolls for each kind of power-up to drop from a brick destroyed at `position`.
+    fn spawn_power_ups(&mut self, position: Vector2<GLfloat>) {
+        for definition in self.power_up_definitions.iter() {
+            if self.random.next_f32() < definition.chance {
+                self.power_ups.push(PowerUp::new(definition, position));
+            }
+        }
+    }
+
+    fn update_power_ups(&mut self, dt: f32) {
+        let mut caught = Vec::new();
+        {
+            let player : &Player = self.player.as_ref().expect("Game error power-ups updated before player initialized");
+
+            for power_up in self.power_ups.iter_mut() {
+                power_up.update(dt);
+
+                if !power_up.as_ref().is_destroyed() && power_up.as_ref().overlaps(player.as_ref()) {
+                    power_up.activate();
+                    caught.push(power_up.as_ref().position().x);
+                }
+            }
+        }
+
+        for x in caught {
+            self.play_sound("power_up", x);
+        }
+
+        self.power_ups.retain(|power_up| !power_up.is_finished());
+        self.apply_power_up_effects();
+    }
+
+    /// Crossfades to the track belonging to the current state and level, if it is not already playing.
+    fn update_music(&mut self) {
+        let track = match self.level_list.as_ref() {
+            Some(list) => match self.state {
+                GameState::GAME_MENU => list.menu_music.clone(),
+                _ => self.current_level.and_then(|index| list.levels[index].music.clone()),
+            },
+            None => return,
+        };
+        if track == self.music {
+            return;
+        }
+
+        {
+            let mut audio = self.audio.borrow_mut();
+            let resources = self.resource_manager.borrow();
+            match track.as_ref() {
+                Some(file) => match resources.locate(file).and_then(|path| open_stream_from_file(&path)) {
+                    Ok(stream) => audio.play_music(stream, MUSIC_VOLUME, MUSIC_CROSSFADE_TIME),
+                    Err(e) => println!("| ERROR::GAME: Could not stream music {} - {}", file, e),
+                },
+                None => audio.stop_music(MUSIC_CROSSFADE_TIME),
+            }
+        }
+        // remembered even on failure, so a missing file is only reported once
+        self.music = track;
+    }
+
+    /// Plays a loaded sound effect, panned to match horizontal position `x` on screen.
+    ///
+    /// Sounds are loaded with the graphics, so a headless game stays silent.
+    fn play_sound(&self, name: &str, x: GLfloat) {
+        let sound = match self.resource_manager.borrow().get_sound(name) {
+            Some(sound) => sound,
+            None => return,
+        };
+        let pan = (x / self.canvas.width()) * 2.0 - 1.0;
+        self.audio.borrow_mut().play(&sound, EFFECT_VOLUME, pan);
+    }
+
+    fn clear_power_ups(&mut self) {
+        self.power_ups.clear();
+        self.apply_power_up_effects();
+    }
+
+    /// Derives every power-up effect from the currently active power-ups.
+    ///
+    /// Effects of the same kind stack, and as each power-up expires its contribution disappears,
+    /// so everything returns to its default once nothing is active.
+    fn apply_power_up_effects(&mut self) {
+        let active = |kind: PowerUpKind| {
+            self.power_ups.iter()
+                .filter(|power_up| power_up.is_activated() && power_up.kind() == kind)
+                .count()
+        };
+        let speed = active(PowerUpKind::SPEED);
+        let sticky = active(PowerUpKind::STICKY) > 0;
+        let pass_through = active(PowerUpKind::PASS_THROUGH) > 0;
+        let pad_size = active(PowerUpKind::PAD_SIZE_INCREASE);
+        let confuse = active(PowerUpKind::CONFUSE) > 0;
+        let chaos = active(PowerUpKind::CHAOS) > 0;
+
+        if let Some(player) = self.player.as_mut() {
+            player.set_width(::objects::player::PLAYER_SIZE_X + PAD_SIZE_INCREASE * pad_size as GLfloat);
+            player.as_mut().set_color(if sticky { Vector3::new(1.0, 0.5, 1.0) } else { Vector3::new(1.0, 1.0, 1.0) });
+        }
+
+        if let Some(ball) = self.ball.as_mut() {
+            ball.set_sticky(sticky);
+            ball.set_pass_through(pass_through);
+            ball.set_speed_multiplier(SPEED_INCREASE.powi(speed as i32));
+            ball.as_mut().set_color(if pass_through { Vector3::new(1.0, 0.5, 0.5) } else { Vector3::new(1.0, 1.0, 1.0) });
+        }
+
+        if let Some(post_processor) = self.post_processor.as_mut() {
+            for &(effect, enabled) in [(Effect::CONFUSE, confuse), (Effect::CHAOS, chaos)].iter() {
+                if enabled {
+                    post_processor.enable(effect);
+                } else {
+                    post_processor.disable(effect);
+                }
+            }
+        }
+    }
+
+
+    /// Draws the game with its OpenGL renderer through the post-processor, placing moving objects
+    /// `alpha` of the way through the current update step.
+    pub fn render(&mut self, alpha: f32) {
+        let mut screen = self.renderer.take().expect("Game error - render called before init");
+
+        unsafe {
+            self.canvas.viewport().apply();
+        }
+
+        if let Some(post_processor) = self.post_processor.as_mut() {
+            post_processor.begin_render();
+        }
 
-        // update player state.
+        self.render_scene(&mut *screen, alpha, true);
+
+        if let Some(post_processor) = self.post_processor.as_mut() {
+            post_processor.end_render();
+            post_processor.render(self.time);
+        }
+
+        self.render_ui(&mut *screen);
+        self.renderer = Some(screen);
     }
 
+    /// Draws the game with `screen`, leaving out the viewport, post-processing and particles, which
+    /// only OpenGL can draw.
+    ///
+    /// With textures loaded into `TextureStorage::CPU`, a headless game can be drawn with the
+    /// software renderer.
+    pub fn render_with(&mut self, screen: &mut Renderer, alpha: f32) {
+        self.render_scene(screen, alpha, false);
+        self.render_ui(screen);
+    }
 
-    pub fn render(&mut self) {
-        let mut screen: &mut SpriteRenderer = self.renderer.as_mut().expect("Game error - render called before init");
+    /// Draws the level and everything in it, along with the particles if `with_particles` is set.
+    fn render_scene(&mut self, screen: &mut Renderer, alpha: f32, with_particles: bool) {
         let mut player : &mut Player = self.player.as_mut().expect("Game error -render called before player initialized");
         let mut ball : &mut BallObject = self.ball.as_mut().expect("Game error render called before ball initialized");
         let state = self.state.clone();
+        // nothing moves outside of play, so there is no step to draw part of the way through
+        let alpha = if state == GameState::GAME_ACTIVE { alpha } else { 1.0 };
+
+        let resources = self.resource_manager.borrow();
+        let texture: Rc<RefCell<Texture>> =
+            resources.get_texture("background")
+                .expect("Game error - could not load background image");
+
+        let size = self.canvas.size();
+        let index = self.current_level.expect("Game error - No Current Level");
+        let mut level = &mut self.levels[index];
 
         match state {
-            GAME_ACTIVE => {
-                let texture: Rc<RefCell<Texture>> =
-                    self.resource_manager.borrow().get_texture("background")
-                        .expect("Game error - could not load background image");
-
-                let height = self.height;
-                let width = self.width;
-                let index = self.current_level.expect("Game error - No Current Level");
-                let mut level = &mut self.levels[index];
+            GameState::GAME_MENU | GameState::GAME_OPTIONS => {
+                // preview the selected level behind a dimmed background
                 screen.draw_sprite_transformed(
                     &texture.borrow(),
                     &Vector2::new(0.0, 0.0),
-                    &Vector2::new(width as f32, height as f32),
+                    &size,
+                    0.0,
+                    &Vector3::new(0.5, 0.5, 0.5),
+                );
+
+                level.draw(screen, &resources);
+            }
+            GameState::GAME_ACTIVE | GameState::GAME_PAUSED | GameState::GAME_OVER | GameState::GAME_WIN => {
+                screen.draw_sprite_transformed(
+                    &texture.borrow(),
+                    &Vector2::new(0.0, 0.0),
+                    &size,
                     0.0,
                     &Vector3::new(1.0, 1.0, 1.0),
                 );
 
-                level.draw(&mut screen);
-                player.as_mut().draw(&mut screen);
-                ball.as_mut().draw(&mut screen);
+                level.draw(screen, &resources);
+                player.as_mut().draw_interpolated(screen, &resources, alpha);
+                for power_up in self.power_ups.iter_mut() {
+                    power_up.as_mut().draw_interpolated(screen, &resources, alpha);
+                }
+                if with_particles {
+                    if let Some(particles) = self.particles.as_mut() {
+                        particles.draw();
+                    }
+                }
+                ball.as_mut().draw_interpolated(screen, &resources, alpha);
             }
-            _ => return
         }
     }
 
+    /// Draws the text over the game, once a font has been set up by `init`.
+    fn render_ui(&mut self, screen: &mut Renderer) {
+        let text: &TextRenderer = match self.text.as_ref() {
+            Some(text) => text,
+            None => return,
+        };
+        let width = self.canvas.width();
+        let height = self.canvas.height();
+        let level = self.current_level.expect("Game error - No Current Level") + 1;
+
+        let mut hud = TextStyle::new();
+        hud.with_scale(0.75);
+        let mut title = TextStyle::new();
+        title.with_alignment(TextAlignment::CENTER)
+            .with_scale(1.5);
+        let mut message = TextStyle::new();
+        message.with_alignment(TextAlignment::CENTER)
+            .with_color(Vector3::new(0.9, 0.9, 0.6))
+            .with_scale(0.75)
+            .with_wrap_width(width * 0.8);
+
+        let center = Vector2::new(width / 2.0, height / 2.0);
+        let below_center = Vector2::new(width / 2.0, height / 2.0 - 60.0);
+
+        let bindings = self.input.bindings();
+        let key = |input: Input| key_name(bindings, input);
+
+        match self.state {
+            GameState::GAME_MENU => {
+                text.draw_text(screen, &format!("Level {} of {}", level, self.levels.len()), &center, &title);
+                text.draw_text(screen, &format!(
+                    "Press {} or {} to choose a level and {} to start. Press {} to change the controls",
+                    key(Input::UP), key(Input::DOWN), key(Input::ACTION0), key(Input::BACK)), &below_center, &message);
+            }
+            GameState::GAME_ACTIVE | GameState::GAME_PAUSED => {
+                let position = Vector2::new(5.0, height - 5.0);
+                text.draw_text(screen, &format!("Lives: {}", self.lives), &position, &hud);
+
+                let mut right = hud.clone();
+                right.with_alignment(TextAlignment::RIGHT);
+                let position = Vector2::new(width - 5.0, height - 5.0);
+                text.draw_text(screen, &format!("Level {}", level), &position, &right);
+
+                if self.state == GameState::GAME_PAUSED {
+                    text.draw_text(screen, "Paused", &center, &title);
+                    text.draw_text(screen, &format!(
+                        "Press {} to resume or {} to return to the menu",
+                        key(Input::PAUSE), key(Input::BACK)), &below_center, &message);
+                }
+            }
+            GameState::GAME_OPTIONS => {
+                let mut position = Vector2::new(width / 2.0, height - 60.0);
+                text.draw_text(screen, "Controls", &position, &title);
+
+                let mut row = hud.clone();
+                row.with_alignment(TextAlignment::CENTER);
+                let mut selected = row.clone();
+                selected.with_color(Vector3::new(1.0, 0.8, 0.3));
+
+                position.y -= 120.0;
+                for (i, input) in INPUTS.iter().enumerate() {
+                    let keys = bindings.keys(*input).iter()
+                        .map(|keycode| keycode.name())
+                        .collect::<Vec<String>>();
+                    let style = if i == self.selected_binding { &selected } else { &row };
+                    text.draw_text(screen, &format!("{}: {}", input.name(), keys.join(", ")), &position, style);
+                    position.y -= 40.0;
+                }
+
+                let style = if self.selected_binding == INPUT_COUNT { &selected } else { &row };
+                text.draw_text(screen, &format!("control scheme: {}", bindings.scheme().name()), &position, style);
+                position.y -= 80.0;
+
+                let hint = if self.is_rebinding {
+                    format!("Press a key to add it to {}, or a bound key to remove it", INPUTS[self.selected_binding].name())
+                } else if self.selected_binding == INPUT_COUNT {
+                    format!("Press {} to switch the control scheme and {} to save and return",
+                        key(Input::ACTION0), key(Input::BACK))
+                } else {
+                    format!("Press {} to change the highlighted action and {} to save and return",
+                        key(Input::ACTION0), key(Input::BACK))
+                };
+                text.draw_text(screen, &hint, &position, &message);
+            }
+            GameState::GAME_OVER => {
+                text.draw_text(screen, "Game Over", &center, &title);
+                text.draw_text(screen, &format!("Press {} to return to the menu", key(Input::ACTION0)), &below_center, &message);
+            }
+            GameState::GAME_WIN => {
+                text.draw_text(screen, "You Won!", &center, &title);
+                text.draw_text(screen, &format!("Press {} to return to the menu", key(Input::ACTION0)), &below_center, &message);
+            }
+        }
+    }
+}
+
+/// Name of the first key bound to `input`, for showing in on-screen prompts.
+fn key_name(bindings: &KeyBindings, input: Input) -> String {
+    bindings.keys(input).first()
+        .map(|keycode| keycode.name())
+        .unwrap_or("(unbound)".to_string())
 }
@@ -1,23 +1,183 @@
 extern crate gl;
+extern crate lewton;
 extern crate nalgebra;
+extern crate png;
 extern crate sdl2;
 
 mod objects;
 mod systems;
 mod utilities;
 mod game;
+mod test_game;
 
 use game::Game;
+use test_game::TestGame;
 use systems::resource_manager::ResourceManager;
+use systems::asset_locator::{AssetLocator, ASSET_DIRECTORY_VARIABLE, user_file};
+use systems::audio_manager::AudioManager;
+use systems::input_manager::ControllerManager;
+use utilities::replay_serialization::{ReplayRecorder, read_replay_from_file};
+use utilities::screenshot::{Screenshot, SCREENSHOT_DIRECTORY, save_screenshot};
+use utilities::settings_serialization::{GameKind, Settings, SETTINGS_FILE, SETTING_NAMES, read_settings_from_file, write_settings_to_file};
 use utilities::timer::Timer;
 
 use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use nalgebra::base::Matrix4;
-use sdl2::event::Event;
+use sdl2::event::{Event, WindowEvent};
+
+/// Seconds of game time simulated by each update.
+const FIXED_TIMESTEP: f64 = 1.0 / 120.0;
+
+/// Longest frame the simulation will catch up on.
+const MAX_FRAME_TIME: f64 = 0.25;
+
+/// Finds the value of a `--name=value` command line argument.
+fn argument(name: &str) -> Option<String> {
+    let prefix = format!("--{}=", name);
+    env::args()
+        .find(|arg| arg.starts_with(&prefix))
+        .map(|arg| arg[prefix.len()..].to_string())
+}
+
+/// Reads the settings file named by `--settings=<file>`, or the first one found with the assets,
+/// then applies any `--setting=value` flags over the top of it.
+///
+/// A missing settings file is created with the defaults, so there is always one to edit. Unless
+/// `--settings` names it, it goes in the user directory rather than next to the assets.
+fn load_settings() -> Settings {
+    let file = argument("settings")
+        .map(PathBuf::from)
+        .or_else(|| AssetLocator::from_environment().locate(SETTINGS_FILE).ok());
+
+    let mut settings = match file {
+        Some(ref file) if file.exists() => match read_settings_from_file(file) {
+            Ok((settings, errors)) => {
+                for e in errors.iter() {
+                    println!("| ERROR::INITIALIZATION: Skipping a line of settings {} - {}", file.display(), e);
+                }
+                settings
+            }
+            Err(e) => {
+                println!("| ERROR::INITIALIZATION: Could not read settings {}, using the defaults - {}", file.display(), e);
+                Settings::new()
+            }
+        },
+        _ => {
+            let settings = Settings::new();
+            let saved = file.map_or_else(|| user_file(SETTINGS_FILE), |file| {
+                    file.parent()
+                        .map_or(Ok(()), |directory| fs::create_dir_all(directory).map_err(|e| e.to_string()))
+                        .map(|_| file)
+                })
+                .and_then(|file| write_settings_to_file(&file, &settings).map(|_| file));
+            match saved {
+                Ok(file) => println!("| INFO::INITIALIZATION: Saved the default settings to {}", file.display()),
+                Err(e) => println!("| ERROR::INITIALIZATION: Could not save the default settings - {}", e),
+            }
+            settings
+        }
+    };
+
+    for name in SETTING_NAMES.iter() {
+        if let Some(value) = argument(name) {
+            if let Err(e) = settings.set(name, &value) {
+                println!("| ERROR::INITIALIZATION: Ignoring --{}={} - {}", name, value, e);
+            }
+        }
+    }
+    settings
+}
+
+fn print_usage() {
+    println!("Usage: gui-base [--flag=value ...]");
+    println!();
+    println!("Settings, overriding those read from the settings file:");
+    println!("  --resolution=<width>x<height>   initial size of the window");
+    println!("  --fullscreen=true|false");
+    println!("  --vsync=true|false");
+    println!("  --gl_version=<major>.<minor>    OpenGL core context to ask for");
+    println!("  --clear_color=<r>, <g>, <b>     colour of the bars around the game");
+    println!("  --scale=fractional|integer      how the game is scaled up to the window");
+    println!("  --asset_directory=<directory>   searched for shaders, textures, levels and sounds first");
+    println!("  --level=menu|<number>           level to start playing straight away");
+    println!("  --game=breakout|test            test draws a triangle, to check OpenGL works");
+    println!();
+    println!("Other flags:");
+    println!("  --settings=<file>               settings file to use, defaulting to {}", SETTINGS_FILE);
+    println!("  --audio=sdl|null|wav:<file>     where sound is played");
+    println!("  --replay=<file>                 play back a recording in place of live input");
+    println!("  --record=<file>                 record input for later playback");
+    println!("  --screenshot-after-frames=<n>   capture the nth frame and quit");
+    println!();
+    println!("Assets are searched for in the user directory, where settings and key bindings are saved,");
+    println!("then in the directories in {}, next to the executable and, in debug builds, in the", ASSET_DIRECTORY_VARIABLE);
+    println!("source tree.");
+}
+
+/// Picks the audio output from a `--audio=sdl|null|wav:<file>` argument, defaulting to SDL.
+///
+/// Falls back to the null backend when the chosen output can not be opened.
+fn create_audio_manager(sdl: &sdl2::Sdl) -> AudioManager {
+    let choice = argument("audio").unwrap_or("sdl".to_string());
+
+    let audio = if choice == "null" {
+        Ok(AudioManager::with_null())
+    } else if choice.starts_with("wav:") {
+        AudioManager::with_wav_file(&choice["wav:".len()..].to_string())
+    } else if choice == "sdl" {
+        sdl.audio().and_then(|audio| AudioManager::with_sdl(&audio))
+    } else {
+        Err(format!("Unknown audio backend {}", choice))
+    };
+
+    audio.unwrap_or_else(|e| {
+        println!("| ERROR::INITIALIZATION: Could not start audio, continuing without sound - {}", e);
+        AudioManager::with_null()
+    })
+}
+
+/// Saves what has been drawn to the window so far to the screenshots directory.
+fn capture_screenshot(window: &sdl2::video::Window) {
+    let (width, height) = window.drawable_size();
+    let screenshot = Screenshot::from_framebuffer(width, height);
+    match save_screenshot(&SCREENSHOT_DIRECTORY, &screenshot) {
+        Ok(path) => println!("| INFO::SCREENSHOT: Saved {}", path.display()),
+        Err(e) => println!("| ERROR::SCREENSHOT: Could not save screenshot - {}", e),
+    }
+}
+
+/// Draws the test triangle until the window is closed.
+fn run_test_game(window: &sdl2::video::Window, event_pump: &mut sdl2::EventPump, resource_manager: &Rc<RefCell<ResourceManager>>) {
+    let mut game = TestGame::new(resource_manager);
+    game.init();
+
+    'main: loop {
+        for event in event_pump.poll_iter() {
+            if let Event::Quit { .. } = event {
+                break 'main;
+            }
+        }
+
+        unsafe {
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+        game.render();
+        window.gl_swap_window();
+    }
+}
 
 fn main() {
+    if env::args().any(|arg| arg == "--help") {
+        print_usage();
+        return;
+    }
+    let settings = load_settings();
+
     // configure SDL2
     let sdl = sdl2::init().unwrap();
     let video_subsystem = sdl
@@ -26,42 +186,110 @@ fn main() {
     let gl_attr = video_subsystem.gl_attr();
 
     gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
-    gl_attr.set_context_version(4, 5);
+    gl_attr.set_context_version(settings.gl_version.0, settings.gl_version.1);
 
-    let window = video_subsystem
-        .window("Game", 900, 700)
+    let mut window_builder = video_subsystem.window("Game", settings.width, settings.height);
+    window_builder
         .opengl()
-        .resizable()
+        .allow_highdpi()
+        .resizable();
+    if settings.fullscreen {
+        window_builder.fullscreen_desktop();
+    }
+    let window = window_builder
         .build()
         .expect("| ERROR::INITIALIZATION: Could not start window.");
 
     let gl_context = window.gl_create_context().unwrap();
     let gl_ =
         gl::load_with(|s| video_subsystem.gl_get_proc_address(s) as *const std::os::raw::c_void);
+    if !video_subsystem.gl_set_swap_interval(if settings.vsync { 1 } else { 0 }) {
+        println!("| ERROR::INITIALIZATION: Could not change vsync, continuing with the driver's default");
+    }
     let mut event_pump = sdl.event_pump().unwrap();
 
+    // the game still runs with just a keyboard if controllers are unavailable
+    let mut controllers = match sdl.game_controller() {
+        Ok(subsystem) => Some(ControllerManager::new(subsystem)),
+        Err(e) => {
+            println!("| ERROR::INITIALIZATION: Could not initialize SDL game controller subsystem - {}", e);
+            None
+        }
+    };
+
     let mut resource_manager = Rc::new(RefCell::new(ResourceManager::new()));
-    let mut game = /* TestGame::new(&resource_manager);*/  Game::new(&resource_manager); 
-    let mut timer = Timer::new();
+    if let Some(directory) = settings.asset_directory.as_ref() {
+        resource_manager.borrow_mut().locator_mut().with_first_directory(directory);
+    }
 
     // configure OpenGL
     unsafe {
-        gl::Viewport(0, 0, 900, 700);
-        gl::ClearColor(0.3, 0.3, 0.5, 1.0);
+        let color = settings.clear_color;
+        gl::ClearColor(color.x, color.y, color.z, 1.0);
         gl::Enable(gl::CULL_FACE);
         gl::Enable(gl::BLEND);
         gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
     }
 
+    if settings.game == GameKind::TEST_GAME {
+        run_test_game(&window, &mut event_pump, &resource_manager);
+        resource_manager.borrow_mut().clear();
+        return;
+    }
+
+    let audio_manager = Rc::new(RefCell::new(create_audio_manager(&sdl)));
+    let mut game = Game::new(&resource_manager, &audio_manager);
+    let mut timer = Timer::new();
+
     game.init();
+    game.set_scale_mode(settings.scale);
+    game.set_window_size(window.size(), window.drawable_size());
+
+    // `--replay=<file>` plays back a recording in place of live input, and `--record=<file>` saves one
+    let replay = argument("replay").map(|file| {
+        let replay = read_replay_from_file(&file)
+            .expect(&format!("| ERROR::INITIALIZATION: Could not read replay {}", file));
+        game.set_seed(replay.seed);
+        game.set_control_scheme(replay.scheme);
+        replay
+    });
+
+    // a replay starts on the level it was recorded on, whatever the settings say
+    let mut level = replay.as_ref().map(|replay| replay.level).unwrap_or(settings.level);
+    if let Some(index) = level {
+        if index < game.level_count() {
+            game.start_level(index);
+        } else {
+            println!("| ERROR::INITIALIZATION: There is no level {}, starting at the menu", index + 1);
+            level = None;
+        }
+    }
+
+    let mut replay = replay.map(|replay| replay.frames.into_iter());
+    let mut recorder = argument("record").map(|file| {
+        ReplayRecorder::new(&file, game.seed(), game.control_scheme(), level)
+            .expect(&format!("| ERROR::INITIALIZATION: Could not create replay {}", file))
+    });
 
-    let mut delta_time = 0.0f32;
-    let mut last_frame = 0.0f32;
+    // `--screenshot-after-frames=<n>` captures the nth drawn frame and then quits, for scripted captures
+    let screenshot_frame = argument("screenshot-after-frames").map(|frames| {
+        frames.parse::<u64>()
+            .expect(&format!("| ERROR::INITIALIZATION: Invalid frame count {}", frames))
+    });
+    let mut frame_count = 0u64;
+
+    let mut last_frame = timer.get_time();
+    let mut accumulator = 0.0f64;
+    // events wait here until a simulation step consumes them, so none are lost on fast frames
+    let mut pending_events = Vec::new();
 
     'main: loop {
         let current_frame = timer.get_time();
-        delta_time = current_frame - last_frame;
+        // after a long stall, slow the game down rather than running a burst of catch-up steps
+        let frame_time = (current_frame - last_frame).min(MAX_FRAME_TIME);
         last_frame = current_frame;
+        accumulator += frame_time;
+
         let mut event_iterator = event_pump .poll_iter();
 
         let events : Vec<_> = event_iterator.collect();
@@ -73,15 +301,72 @@ fn main() {
         }
 
 
-        game.processInput(delta_time, &events);
+        let resized = events.iter().any(|event| match event {
+            Event::Window { win_event: WindowEvent::Resized(..), .. } |
+            Event::Window { win_event: WindowEvent::SizeChanged(..), .. } => true,
+            _ => false,
+        });
+        if resized {
+            game.set_window_size(window.size(), window.drawable_size());
+        }
+
+        if let Some(controllers) = controllers.as_mut() {
+            controllers.update(&events);
+        }
+        pending_events.extend(events);
+
+        while accumulator >= FIXED_TIMESTEP {
+            accumulator -= FIXED_TIMESTEP;
+            let delta_time = FIXED_TIMESTEP as f32;
+
+            match replay.as_mut() {
+                Some(frames) => match frames.next() {
+                    // steps of any other length would play the recording back differently
+                    Some(ref frame) if frame.dt != delta_time => {
+                        println!("| ERROR::REPLAY: Replay step of {} seconds does not match the fixed step of {}", frame.dt, delta_time);
+                        break 'main;
+                    }
+                    Some(frame) => game.replay_input(frame.dt, frame.input),
+                    None => {
+                        println!("| INFO::REPLAY: Replay finished");
+                        break 'main;
+                    }
+                },
+                None => game.processInput(delta_time, &pending_events),
+            }
+            pending_events.clear();
+
+            let recorded = recorder.as_mut().map(|recorder| recorder.record(delta_time, game.input_frame()));
+            if let Some(Err(e)) = recorded {
+                println!("| ERROR::REPLAY: Could not record frame, recording stopped - {}", e);
+                recorder = None;
+            }
 
-        game.update(delta_time);
+            game.update(delta_time);
+            audio_manager.borrow_mut().update(delta_time);
+        }
+
+        let relative = game.wants_relative_mouse();
+        if sdl.mouse().relative_mouse_mode() != relative {
+            sdl.mouse().set_relative_mouse_mode(relative);
+        }
 
         unsafe {
             gl::Clear(gl::COLOR_BUFFER_BIT);
         }
 
-        game.render();
+        // draw moving objects part way between the last two steps, by the time left over
+        game.render((accumulator / FIXED_TIMESTEP) as f32);
+        frame_count += 1;
+
+        // read back before swapping, while the frame is still in the back buffer
+        if game.take_screenshot_request() {
+            capture_screenshot(&window);
+        }
+        if screenshot_frame == Some(frame_count) {
+            capture_screenshot(&window);
+            break 'main;
+        }
 
         window.gl_swap_window();
     }
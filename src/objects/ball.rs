@@ -1,25 +1,62 @@
 extern crate gl;
 extern crate nalgebra;
 
-use super::game_object::{GameObject, GameObjectBuilder};
-use utilities::texture::Texture;
+use super::game_object::GameObject;
+use super::collision::{check_circle_aabb, Collision};
+use utilities::canvas::{VIRTUAL_WIDTH, VIRTUAL_HEIGHT};
 
 use std::convert::{AsRef, AsMut};
-use std::rc::Rc;
-use std::cell::RefCell;
 
-use gl::types::{GLfloat, GLuint};
+use gl::types::GLfloat;
 use nalgebra::base::{Vector2};
 
-
 pub const BALL_RADIUS : GLfloat = 12.5;
 pub const BALL_VELOCITY_X : GLfloat = 100.0;
 pub const BALL_VELOCITY_Y : GLfloat = 350.0;
+pub const PADDLE_BOUNCE_STRENGTH : GLfloat = 2.0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pushes_a_ball_with_its_centre_inside_a_brick_out_of_the_nearest_side() {
+        let mut brick = GameObject::new("block");
+        brick.with_position(Vector2::new(0.0, 0.0)).with_size(Vector2::new(100.0, 20.0));
+        let brick = brick.build();
+        // centre just below the middle of the brick, moving up into it
+        let mut ball = BallObject::new(Vector2::new(50.0 - BALL_RADIUS, 8.0 - BALL_RADIUS), BALL_RADIUS, Vector2::new(0.0, 100.0), "face");
+
+        let collision = ball.check_collision(&brick).expect("Should collide");
+        ball.resolve_collision(&collision);
+
+        assert!(ball.as_ref().velocity.y < 0.0);
+        assert!(ball.check_collision(&brick).is_none());
+    }
+
+    #[test]
+    fn pushes_a_ball_near_the_end_of_a_brick_out_of_the_top_rather_than_the_end() {
+        let mut brick = GameObject::new("block");
+        brick.with_position(Vector2::new(0.0, 0.0)).with_size(Vector2::new(100.0, 20.0));
+        let brick = brick.build();
+        let mut ball = BallObject::new(Vector2::new(90.0 - BALL_RADIUS, 12.0 - BALL_RADIUS), BALL_RADIUS, Vector2::new(0.0, -100.0), "face");
+
+        let collision = ball.check_collision(&brick).expect("Should collide");
+        ball.resolve_collision(&collision);
+
+        assert_eq!(ball.center().x, 90.0);
+        assert!(ball.as_ref().velocity.y > 0.0);
+        assert!(ball.check_collision(&brick).is_none());
+    }
+}
 
 pub struct BallObject {
     pub(super) object: GameObject,
     pub(super) radius: GLfloat,
-    pub(super) is_stuck: bool
+    pub(super) is_stuck: bool,
+    pub(super) is_sticky: bool,           // stick to the paddle when it is hit
+    pub(super) is_pass_through: bool,     // pass through non-solid bricks
+    pub(super) speed_multiplier: GLfloat
 }
 
 
@@ -37,49 +74,138 @@ impl AsRef<GameObject> for BallObject {
 }
 
 impl BallObject {
-    pub fn new(position: Vector2<GLfloat>, radius: GLfloat, velocity: Vector2<GLfloat>, sprite: &Rc<RefCell<Texture>>) -> Self {
+    pub fn new(position: Vector2<GLfloat>, radius: GLfloat, velocity: Vector2<GLfloat>, sprite: &str) -> Self {
        let mut builder = GameObject::new(sprite); 
        builder.with_position(position)
+              .with_size(Vector2::new(radius * 2.0, radius * 2.0))
               .with_velocity(velocity);
        let builder = builder.build();
 
        BallObject {
             object: builder,
             radius: radius,
-            is_stuck: false
+            is_stuck: false,
+            is_sticky: false,
+            is_pass_through: false,
+            speed_multiplier: 1.0
        }
 
     }
 
-    pub fn update(&mut self, dt : f32, window_width: GLuint, window_height: GLuint) -> &Vector2<GLfloat> {
-        let window_width : f32 = window_width as f32;
-        let window_height : f32 = window_height as f32;
-
+    pub fn update(&mut self, dt : f32) -> &Vector2<GLfloat> {
         if !self.is_stuck {
             self.object.position += self.object.velocity * dt;            
 
             if self.object.position.x <= 0.0 {
                 self.object.velocity.x *= -1.0;
                 self.object.position.x = 0.0;
-            } else if self.object.position.x + self.object.size.x >= window_width {
+            } else if self.object.position.x + self.object.size.x >= VIRTUAL_WIDTH {
                 self.object.velocity.x *= -1.0;
-                self.object.position.x = window_width - self.object.size.x;
+                self.object.position.x = VIRTUAL_WIDTH - self.object.size.x;
             }
-            if self.object.position.y >= window_height {
-                self.object.velocity.y *= -1.0;
-                self.object.position.x = window_height;
+            if self.object.position.y + self.object.size.y >= VIRTUAL_HEIGHT {
+                self.object.velocity.y = -self.object.velocity.y.abs();
+                self.object.position.y = VIRTUAL_HEIGHT - self.object.size.y;
             }
         }
         &self.object.position
     }
 
 
+    pub fn is_stuck(&self) -> bool {
+        self.is_stuck
+    }
+
+    /// Releases a ball that is stuck to the paddle.
+    pub fn launch(&mut self) {
+        self.is_stuck = false;
+    }
+
+    /// Keeps a stuck ball resting on the centre of the paddle.
+    pub fn follow(&mut self, paddle: &GameObject) {
+        if self.is_stuck {
+            self.object.position.x = paddle.position.x + paddle.size.x / 2.0 - self.radius;
+            self.object.position.y = paddle.position.y + paddle.size.y;
+        }
+    }
+
+    /// Returns true once the ball has completely left the bottom of the screen.
+    pub fn is_out_of_bounds(&self) -> bool {
+        self.object.position.y + self.object.size.y < 0.0
+    }
+
+    pub fn center(&self) -> Vector2<GLfloat> {
+        self.object.position + Vector2::new(self.radius, self.radius)
+    }
+
+    pub fn check_collision(&self, other: &GameObject) -> Option<Collision> {
+        check_circle_aabb(&self.center(), self.radius, &other.position, &other.size)
+    }
+
+    /// Reflects the ball off the face described by `collision` and moves it out of the object.
+    pub fn resolve_collision(&mut self, collision: &Collision) {
+        // the side is taken from the centres rather than `difference`, which is zero once the
+        // ball's centre is inside the object
+        if collision.direction.is_horizontal() {
+            let penetration = collision.half_extents.x + self.radius - collision.offset.x.abs();
+            let sign = collision.offset.x.signum();
+
+            self.object.velocity.x = sign * self.object.velocity.x.abs();
+            self.object.position.x += sign * penetration;
+        } else {
+            let penetration = collision.half_extents.y + self.radius - collision.offset.y.abs();
+            let sign = collision.offset.y.signum();
+
+            self.object.velocity.y = sign * self.object.velocity.y.abs();
+            self.object.position.y += sign * penetration;
+        }
+    }
+
+    /// Bounces the ball off the paddle, steering it by how far from the paddle centre it landed.
+    ///
+    /// The speed of the ball is preserved and the ball is always sent upwards, so it can never
+    /// get trapped inside the paddle.
+    pub fn bounce_off_paddle(&mut self, paddle: &GameObject) {
+        let half_width = paddle.size.x / 2.0;
+        let paddle_center = paddle.position.x + half_width;
+        let distance = self.center().x - paddle_center;
+        let percentage = (distance / half_width).max(-1.0).min(1.0);
+
+        let speed = self.object.velocity.norm();
+        let direction = Vector2::new(
+            BALL_VELOCITY_X * percentage * PADDLE_BOUNCE_STRENGTH,
+            self.object.velocity.y.abs(),
+        );
+
+        self.object.velocity = direction.normalize() * speed;
+        self.object.position.y = paddle.position.y + paddle.size.y;
+        self.is_stuck = self.is_sticky;
+    }
+
+    pub fn is_pass_through(&self) -> bool {
+        self.is_pass_through
+    }
+
+    pub fn set_sticky(&mut self, is_sticky: bool) {
+        self.is_sticky = is_sticky;
+    }
+
+    pub fn set_pass_through(&mut self, is_pass_through: bool) {
+        self.is_pass_through = is_pass_through;
+    }
+
+    /// Scales the ball's speed relative to its base speed, undoing any previous multiplier.
+    pub fn set_speed_multiplier(&mut self, speed_multiplier: GLfloat) {
+        self.object.velocity *= speed_multiplier / self.speed_multiplier;
+        self.speed_multiplier = speed_multiplier;
+    }
+
     pub fn reset(&mut self, position: &Vector2<GLfloat>, velocity: &Vector2<GLfloat>) {
         self.object.position.x = position.x;
         self.object.position.y = position.y;
 
-        self.object.velocity.x = velocity.x;
-        self.object.velocity.y = velocity.y;
+        self.object.velocity.x = velocity.x * self.speed_multiplier;
+        self.object.velocity.y = velocity.y * self.speed_multiplier;
 
         self.is_stuck = true;
     }
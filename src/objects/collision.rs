@@ -0,0 +1,162 @@
+extern crate gl;
+extern crate nalgebra;
+
+use gl::types::GLfloat;
+use nalgebra::base::Vector2;
+
+/// Compass direction of the side of an object that was hit.
+///
+/// Note that the world uses an upward pointing y axis (see the projection in `Game::init`),
+/// so `UP` is the top face of the object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    UP,
+    RIGHT,
+    DOWN,
+    LEFT,
+}
+
+/// Result of a circle-vs-AABB collision test.
+#[derive(Debug, Clone)]
+pub struct Collision {
+    pub direction: Direction,               // side of the box the circle hit
+    pub difference: Vector2<GLfloat>,       // vector from the closest point on the box to the circle centre
+    pub offset: Vector2<GLfloat>,           // vector from the box centre to the circle centre
+    pub half_extents: Vector2<GLfloat>,     // half the size of the box
+}
+
+impl Direction {
+    /// Returns the direction of the compass vector closest to `target`.
+    pub fn from_vector(target: &Vector2<GLfloat>) -> Direction {
+        let compass = [
+            (Direction::UP, Vector2::new(0.0, 1.0)),
+            (Direction::RIGHT, Vector2::new(1.0, 0.0)),
+            (Direction::DOWN, Vector2::new(0.0, -1.0)),
+            (Direction::LEFT, Vector2::new(-1.0, 0.0)),
+        ];
+
+        let norm = target.norm();
+        if norm == 0.0 {
+            return Direction::UP;
+        }
+        let target = target / norm;
+
+        let mut best = Direction::UP;
+        let mut max = GLfloat::NEG_INFINITY;
+        for &(direction, ref vector) in compass.iter() {
+            let dot = target.dot(vector);
+            if dot > max {
+                max = dot;
+                best = direction;
+            }
+        }
+        best
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        match *self {
+            Direction::LEFT | Direction::RIGHT => true,
+            _ => false,
+        }
+    }
+}
+
+/// Checks a circle against an axis aligned box.
+///
+/// `box_position` is the bottom left corner of the box, matching `GameObject::position`.
+pub fn check_circle_aabb(center: &Vector2<GLfloat>,
+                         radius: GLfloat,
+                         box_position: &Vector2<GLfloat>,
+                         box_size: &Vector2<GLfloat>) -> Option<Collision> {
+    let half_extents = box_size * 0.5;
+    let box_center = box_position + half_extents;
+
+    // clamp the vector between the centres to the box to find the closest point to the circle
+    let offset = center - box_center;
+    let clamped = Vector2::new(
+        offset.x.max(-half_extents.x).min(half_extents.x),
+        offset.y.max(-half_extents.y).min(half_extents.y),
+    );
+    let closest = box_center + clamped;
+    let difference = center - closest;
+
+    if difference.norm() < radius {
+        // if the centre has already entered the box, it hit whichever face it is nearest to
+        let direction = if difference.norm() > 0.0 {
+            Direction::from_vector(&difference)
+        } else if half_extents.x - offset.x.abs() < half_extents.y - offset.y.abs() {
+            if offset.x < 0.0 { Direction::LEFT } else { Direction::RIGHT }
+        } else {
+            if offset.y < 0.0 { Direction::DOWN } else { Direction::UP }
+        };
+        Some(Collision {
+            direction: direction,
+            difference: difference,
+            offset: offset,
+            half_extents: half_extents,
+        })
+    } else {
+        None
+    }
+}
+
+/// Checks two axis aligned boxes for overlap.
+pub fn check_aabb(first_position: &Vector2<GLfloat>,
+                  first_size: &Vector2<GLfloat>,
+                  second_position: &Vector2<GLfloat>,
+                  second_size: &Vector2<GLfloat>) -> bool {
+    let overlap_x = first_position.x + first_size.x >= second_position.x
+        && second_position.x + second_size.x >= first_position.x;
+    let overlap_y = first_position.y + first_size.y >= second_position.y
+        && second_position.y + second_size.y >= first_position.y;
+    overlap_x && overlap_y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_hit_on_top_face() {
+        let collision = check_circle_aabb(
+            &Vector2::new(5.0, 12.0), 3.0,
+            &Vector2::new(0.0, 0.0), &Vector2::new(10.0, 10.0))
+            .expect("Should collide");
+        assert_eq!(collision.direction, Direction::UP);
+        assert!((collision.difference.y - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn detects_hit_on_left_face() {
+        let collision = check_circle_aabb(
+            &Vector2::new(-2.0, 5.0), 3.0,
+            &Vector2::new(0.0, 0.0), &Vector2::new(10.0, 10.0))
+            .expect("Should collide");
+        assert_eq!(collision.direction, Direction::LEFT);
+    }
+
+    #[test]
+    fn picks_the_nearest_face_once_the_centre_is_inside() {
+        // closer to the right end than to the middle, but only 8 units below the top face
+        let collision = check_circle_aabb(
+            &Vector2::new(90.0, 12.0), 3.0,
+            &Vector2::new(0.0, 0.0), &Vector2::new(100.0, 20.0))
+            .expect("Should collide");
+        assert_eq!(collision.direction, Direction::UP);
+    }
+
+    #[test]
+    fn detects_overlapping_boxes() {
+        assert!(check_aabb(&Vector2::new(0.0, 0.0), &Vector2::new(10.0, 10.0),
+                           &Vector2::new(5.0, 5.0), &Vector2::new(10.0, 10.0)));
+        assert!(!check_aabb(&Vector2::new(0.0, 0.0), &Vector2::new(10.0, 10.0),
+                            &Vector2::new(11.0, 0.0), &Vector2::new(10.0, 10.0)));
+    }
+
+    #[test]
+    fn ignores_distant_circle() {
+        assert!(check_circle_aabb(
+            &Vector2::new(14.0, 14.0), 3.0,
+            &Vector2::new(0.0, 0.0), &Vector2::new(10.0, 10.0)).is_none());
+    }
+}
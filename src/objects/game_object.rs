@@ -1,21 +1,20 @@
 
-use utilities::sprite_renderer::SpriteRenderer;
-use utilities::texture::Texture;
-
-use std::cell::RefCell;
-use std::rc::Rc;
+use super::collision::check_aabb;
+use systems::resource_manager::ResourceManager;
+use utilities::renderer::Renderer;
 
 use gl::types::GLfloat;
 use nalgebra::base::{Matrix4, Unit, Vector2, Vector3, Vector4};
 
 pub struct GameObject {
     pub(super) position: Vector2<GLfloat>,
+    pub(super) previous_position: Vector2<GLfloat>,   // position at the start of the current update
     pub(super) size: Vector2<GLfloat>,
     pub(super) velocity: Vector2<GLfloat>,
     pub(super) rotation: GLfloat,
     pub(super) is_solid: bool,
     pub(super) is_destroyed: bool,
-    pub(super) sprite: Rc<RefCell<Texture>>,
+    pub(super) sprite: String,          // name of the texture, looked up when drawn
     pub(super) color: Vector3<GLfloat>,
 }
 
@@ -26,12 +25,12 @@ pub struct GameObjectBuilder {
     rotation: Option<GLfloat>,
     is_solid: Option<bool>,
     is_destroyed: Option<bool>,
-    sprite: Rc<RefCell<Texture>>,
+    sprite: String,
     color: Option<Vector3<GLfloat>>,
 }
 
 impl GameObjectBuilder {
-    fn new(sprite: &Rc<RefCell<Texture>>) -> Self {
+    fn new(sprite: &str) -> Self {
         GameObjectBuilder {
             position: None,
             size: None,
@@ -40,7 +39,7 @@ impl GameObjectBuilder {
             is_solid: None,
             is_destroyed: None,
             color: None,
-            sprite: sprite.clone(),
+            sprite: sprite.to_string(),
         }
     }
 
@@ -94,7 +93,7 @@ impl GameObjectBuilder {
 }
 
 impl GameObject {
-    pub fn new(sprite: &Rc<RefCell<Texture>>) -> GameObjectBuilder {
+    pub fn new(sprite: &str) -> GameObjectBuilder {
         GameObjectBuilder::new(sprite)
     }
 
@@ -105,11 +104,12 @@ impl GameObject {
         rotation: GLfloat,
         is_solid: bool,
         is_destroyed: bool,
-        sprite: Rc<RefCell<Texture>>,
+        sprite: String,
         color: Vector3<GLfloat>,
     ) -> Self {
         GameObject {
             position: position,
+            previous_position: position,
             size: size,
             velocity: velocity,
             rotation: rotation,
@@ -134,6 +134,18 @@ impl GameObject {
     }
 
 
+    pub fn is_solid(&self) -> bool {
+        self.is_solid
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.is_destroyed
+    }
+
+    pub fn destroy(&mut self) {
+        self.is_destroyed = true;
+    }
+
     pub fn is_completed(&self) -> bool {
         self.is_solid || self.is_destroyed
     }
@@ -142,14 +154,61 @@ impl GameObject {
         &self.position
     }
 
+    pub fn size(&self) -> &Vector2<GLfloat> {
+        &self.size
+    }
+
+    pub fn velocity(&self) -> &Vector2<GLfloat> {
+        &self.velocity
+    }
+
+    pub fn sprite(&self) -> &str {
+        &self.sprite
+    }
+
+    pub fn color(&self) -> &Vector3<GLfloat> {
+        &self.color
+    }
+
+    pub fn set_color(&mut self, color: Vector3<GLfloat>) {
+        self.color = color;
+    }
+
+    pub fn overlaps(&self, other: &GameObject) -> bool {
+        check_aabb(&self.position, &self.size, &other.position, &other.size)
+    }
+
     pub fn position_mut(&mut self) -> &mut Vector2<GLfloat> {
         &mut self.position
     }
 
 
-    pub fn draw(&mut self, renderer: &mut SpriteRenderer) {
+    pub fn draw(&mut self, renderer: &mut Renderer, resources: &ResourceManager) {
+        let position = self.position;
+        self.draw_at(renderer, resources, &position);
+    }
+
+    /// Marks the current position as where the next update starts from.
+    pub fn save_position(&mut self) {
+        self.previous_position = self.position;
+    }
+
+    /// Position `alpha` of the way from where the object was before the last update to where it is now.
+    pub fn interpolated_position(&self, alpha: GLfloat) -> Vector2<GLfloat> {
+        self.previous_position + (self.position - self.previous_position) * alpha
+    }
+
+    /// Draws the object at its interpolated position.
+    pub fn draw_interpolated(&mut self, renderer: &mut Renderer, resources: &ResourceManager, alpha: GLfloat) {
+        let position = self.interpolated_position(alpha);
+        self.draw_at(renderer, resources, &position);
+    }
+
+    fn draw_at(&mut self, renderer: &mut Renderer, resources: &ResourceManager, position: &Vector2<GLfloat>) {
         if !self.is_destroyed  {
-            renderer.draw_sprite_transformed(&self.sprite.borrow(), &self.position, &self.size, self.rotation, &self.color);
+            let texture = resources.get_texture(&self.sprite)
+                .expect(&format!("Game error - could not load texture {}", self.sprite));
+            renderer.draw_sprite_transformed(&texture.borrow(), position, &self.size, self.rotation, &self.color);
         }
     }
 }
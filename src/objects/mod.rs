@@ -1,3 +1,5 @@
 pub mod ball;
+pub mod collision;
 pub mod game_object;
 pub mod player;
+pub mod power_up;
@@ -1,10 +1,7 @@
-use objects::game_object::{GameObject, GameObjectBuilder};
-use utilities::texture::Texture;
-use utilities::shader::Shader;
+use objects::game_object::GameObject;
+use utilities::canvas::VIRTUAL_WIDTH;
 
 use std::convert::{AsRef,AsMut};
-use std::rc::Rc;
-use std::cell::RefCell;
 
 
 use nalgebra::base::{Vector2};
@@ -12,62 +9,62 @@ use gl::types::{GLfloat};
 
 pub struct Player {
     pub(super) object: GameObject,
-    pub(super) screen_width: GLfloat, 
-    pub(super) screen_height: GLfloat
 }
 
-pub static PLAYER_VELOCITY: GLfloat = 5.0;
+pub static PLAYER_VELOCITY: GLfloat = 500.0;
 pub static PLAYER_SIZE_X: GLfloat = 100.0;
 pub static PLAYER_SIZE_Y: GLfloat = 20.0;
 
 impl Player {
 
-    pub fn new(position : Vector2<GLfloat>, (screen_width, screen_height): (GLfloat, GLfloat), sprite: &Rc<RefCell<Texture>>) -> Player {
+    pub fn new(position : Vector2<GLfloat>, sprite: &str) -> Player {
         let mut object = GameObject::new(sprite);
         object
             .with_position(position)
             .with_size(Vector2::new(PLAYER_SIZE_X, PLAYER_SIZE_Y));
         Player {
             object:  object.build(),
-            screen_width: screen_width,
-            screen_height: screen_height
         }
     }
 
-    pub fn move_left(&mut self, dt: GLfloat) {
+    /// Returns the paddle to `position` at its default size.
+    pub fn reset(&mut self, position: Vector2<GLfloat>) {
+        let rotation = self.object.get_rotation();
+        self.object.update_transform(&position, &Vector2::new(PLAYER_SIZE_X, PLAYER_SIZE_Y), rotation);
+    }
 
-        let velocity = PLAYER_VELOCITY * dt;
-        let x = self.object.position().x; 
-        println!("Moving left with position {}", x);
+    /// Resizes the paddle around its centre, keeping it on screen.
+    pub fn set_width(&mut self, width: GLfloat) {
+        let center = self.object.position.x + self.object.size.x / 2.0;
+        let x = (center - width / 2.0).max(0.0).min(VIRTUAL_WIDTH - width);
 
-        if x >= 0.0 {
-            self.object.position_mut().x -= velocity;
-        }
+        self.object.size.x = width;
+        self.object.position.x = x;
+    }
 
-        if x <= 0.0 {
-            self.object.position_mut().x = 0.0;
-        }
+    /// Centres the paddle on `x`, keeping it within the screen.
+    pub fn move_to(&mut self, x: GLfloat) {
+        let bound = VIRTUAL_WIDTH - self.object.size().x;
+        let x = x - self.object.size().x / 2.0;
 
-        println!("position is now {}", self.object.position().x);
+        self.object.position_mut().x = x.max(0.0).min(bound);
     }
 
-    pub fn move_right(&mut self, dt: GLfloat) {
-        let velocity = PLAYER_VELOCITY * dt;
-        let x = self.object.position().x; 
-        println!("Moving right with position {}", x);
-        let bound = self.screen_width - PLAYER_SIZE_X;
+    /// Moves horizontally at `amount` times the full paddle speed, where negative is to the left.
+    pub fn move_by(&mut self, amount: GLfloat, dt: GLfloat) {
+        let velocity = PLAYER_VELOCITY * amount.max(-1.0).min(1.0) * dt;
+        let x = self.object.position().x + velocity;
+        let bound = VIRTUAL_WIDTH - self.object.size().x;
 
+        self.object.position_mut().x = x.max(0.0).min(bound);
+    }
 
-        if x <=  bound {
-            self.object.position_mut().x += velocity;
-        }
-        if x >=  bound {
-            self.object.position_mut().x = bound;
-        }
+}
 
-        println!("position is now {}", self.object.position().x);
+impl AsRef<GameObject> for Player {
+    fn as_ref(&self) -> &GameObject {
+        &self.object
     }
-
 }
 
 impl AsMut<GameObject> for Player {
@@ -0,0 +1,118 @@
+extern crate gl;
+extern crate nalgebra;
+
+use super::game_object::GameObject;
+
+use std::convert::{AsRef, AsMut};
+
+use gl::types::GLfloat;
+use nalgebra::base::{Vector2, Vector3};
+
+pub const POWER_UP_SIZE_X : GLfloat = 60.0;
+pub const POWER_UP_SIZE_Y : GLfloat = 20.0;
+pub const POWER_UP_VELOCITY_Y : GLfloat = -150.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PowerUpKind {
+    SPEED,
+    STICKY,
+    PASS_THROUGH,
+    PAD_SIZE_INCREASE,
+    CONFUSE,
+    CHAOS,
+}
+
+impl PowerUpKind {
+    pub fn from_name(name: &str) -> Option<PowerUpKind> {
+        match name {
+            "speed" => Some(PowerUpKind::SPEED),
+            "sticky" => Some(PowerUpKind::STICKY),
+            "pass_through" => Some(PowerUpKind::PASS_THROUGH),
+            "pad_size" => Some(PowerUpKind::PAD_SIZE_INCREASE),
+            "confuse" => Some(PowerUpKind::CONFUSE),
+            "chaos" => Some(PowerUpKind::CHAOS),
+            _ => None,
+        }
+    }
+}
+
+/// Data describing one kind of power-up, as read from the power-up config file.
+#[derive(Debug, Clone)]
+pub struct PowerUpDefinition {
+    pub kind: PowerUpKind,
+    pub chance: GLfloat,        // probability of dropping from a destroyed brick
+    pub duration: GLfloat,      // seconds the effect lasts once caught
+    pub texture: String,        // name of the loaded texture to draw it with
+    pub color: Vector3<GLfloat>,
+}
+
+pub struct PowerUp {
+    pub(super) object: GameObject,
+    pub(super) kind: PowerUpKind,
+    pub(super) duration: GLfloat,
+    pub(super) is_activated: bool,
+}
+
+impl AsMut<GameObject> for PowerUp {
+    fn as_mut(&mut self) -> &mut GameObject {
+        &mut self.object
+    }
+}
+
+impl AsRef<GameObject> for PowerUp {
+    fn as_ref(&self) -> &GameObject {
+        &self.object
+    }
+}
+
+impl PowerUp {
+    pub fn new(definition: &PowerUpDefinition, position: Vector2<GLfloat>) -> Self {
+        let mut builder = GameObject::new(&definition.texture);
+        builder.with_position(position)
+               .with_size(Vector2::new(POWER_UP_SIZE_X, POWER_UP_SIZE_Y))
+               .with_velocity(Vector2::new(0.0, POWER_UP_VELOCITY_Y))
+               .with_color(definition.color.clone());
+
+        PowerUp {
+            object: builder.build(),
+            kind: definition.kind,
+            duration: definition.duration,
+            is_activated: false,
+        }
+    }
+
+    pub fn kind(&self) -> PowerUpKind {
+        self.kind
+    }
+
+    pub fn is_activated(&self) -> bool {
+        self.is_activated
+    }
+
+    /// A power-up is finished once it has either been missed or has run out.
+    pub fn is_finished(&self) -> bool {
+        self.object.is_destroyed && !self.is_activated
+    }
+
+    /// Catches the power-up, hiding it and starting its timer.
+    pub fn activate(&mut self) {
+        self.is_activated = true;
+        self.object.is_destroyed = true;
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if self.is_activated {
+            self.duration -= dt;
+            if self.duration <= 0.0 {
+                self.is_activated = false;
+            }
+        } else if !self.object.is_destroyed {
+            self.object.position += self.object.velocity * dt;
+
+            // missed by the paddle
+            if self.object.position.y + self.object.size.y < 0.0 {
+                self.object.is_destroyed = true;
+            }
+        }
+    }
+}
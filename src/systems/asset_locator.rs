@@ -0,0 +1,145 @@
+extern crate sdl2;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sdl2::filesystem::pref_path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_assets_in_the_first_directory_holding_them() {
+        let root = env::temp_dir().join(format!("asset_locator_{}", ::std::process::id()));
+        for directory in ["empty", "assets", "later"].iter() {
+            fs::create_dir_all(root.join(directory).join("shaders")).expect("Should work");
+        }
+        fs::write(root.join("assets/shaders/sprite.vs"), "").expect("Should work");
+        fs::write(root.join("later/shaders/sprite.vs"), "").expect("Should work");
+
+        let mut locator = AssetLocator::new();
+        locator.with_directory(root.join("empty")).with_directory(root.join("assets")).with_directory(root.join("later"));
+        let path = locator.locate("shaders/sprite.vs");
+        fs::remove_dir_all(&root).expect("Should work");
+
+        assert_eq!(path, Ok(root.join("assets/shaders/sprite.vs")));
+    }
+
+    #[test]
+    fn lists_every_searched_location_when_missing() {
+        let mut locator = AssetLocator::new();
+        locator.with_directory("nowhere").with_directory("elsewhere");
+
+        let e = locator.locate("textures/missing.png").unwrap_err();
+        assert!(e.contains(&Path::new("nowhere").join("textures/missing.png").display().to_string()));
+        assert!(e.contains(&Path::new("elsewhere").join("textures/missing.png").display().to_string()));
+    }
+}
+
+/// Environment variable naming a directory searched for assets before any other, bar the user directory.
+pub const ASSET_DIRECTORY_VARIABLE: &str = "BREAKOUT_ASSETS";
+
+/// Names SDL picks the user directory from.
+const USER_ORGANIZATION: &str = "rust-breakout";
+const USER_APPLICATION: &str = "breakout";
+
+/// The per-user directory settings and key bindings are saved in, which SDL creates if needed.
+pub fn user_directory() -> Result<PathBuf, String> {
+    pref_path(USER_ORGANIZATION, USER_APPLICATION)
+        .map(PathBuf::from)
+        .map_err(|e| format!("Could not find the user directory - {}", e))
+}
+
+/// Where the user's own copy of the file `name`, such as `config/bindings.cfg`, is saved, creating
+/// the directories it goes in.
+pub fn user_file(name: &str) -> Result<PathBuf, String> {
+    let path = user_directory()?.join(name);
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+    }
+    Ok(path)
+}
+
+/// Finds asset files, such as `shaders/sprite.vs`, by trying each directory of a search path in turn.
+pub struct AssetLocator {
+    search_path: Vec<PathBuf>,
+}
+
+impl AssetLocator {
+    /// A locator with nothing to search.
+    pub fn new() -> Self {
+        AssetLocator {
+            search_path: Vec::new(),
+        }
+    }
+
+    /// Searches, in order, the user directory, the directories in `BREAKOUT_ASSETS`, the directory
+    /// holding the executable and, in debug builds, the directory of the crate it was built from.
+    pub fn from_environment() -> Self {
+        let mut locator = AssetLocator::new();
+
+        // files the player saved override the ones shipped with the game
+        if let Ok(directory) = user_directory() {
+            locator.with_directory(directory);
+        }
+
+        if let Some(directories) = env::var_os(ASSET_DIRECTORY_VARIABLE) {
+            for directory in env::split_paths(&directories) {
+                locator.with_directory(directory);
+            }
+        }
+
+        if let Some(directory) = env::current_exe().ok().and_then(|exe| exe.parent().map(|parent| parent.to_path_buf())) {
+            locator.with_directory(directory);
+        }
+
+        // so `cargo run` finds the assets in the source tree, wherever it is run from
+        if cfg!(debug_assertions) {
+            if let Some(directory) = option_env!("CARGO_MANIFEST_DIR") {
+                locator.with_directory(directory);
+            }
+        }
+
+        locator
+    }
+
+    /// Adds a directory to the end of the search path.
+    pub fn with_directory<T : AsRef<Path>>(&mut self, directory: T) -> &mut Self {
+        self.search_path.push(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// Adds a directory to the start of the search path, to be searched before all the others.
+    pub fn with_first_directory<T : AsRef<Path>>(&mut self, directory: T) -> &mut Self {
+        self.search_path.insert(0, directory.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn search_path(&self) -> &Vec<PathBuf> {
+        &self.search_path
+    }
+
+    /// Finds the file `name` in the first directory of the search path that has it.
+    ///
+    /// Absolute names are used as they are.
+    pub fn locate(&self, name: &str) -> Result<PathBuf, String> {
+        let name = Path::new(name);
+        let candidates = if name.is_absolute() {
+            vec![name.to_path_buf()]
+        } else {
+            self.search_path.iter().map(|directory| directory.join(name)).collect()
+        };
+
+        if let Some(path) = candidates.iter().find(|path| path.is_file()) {
+            return Ok(path.clone());
+        }
+
+        let searched = candidates.iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<String>>();
+        Err(format!("Could not find asset {} - searched {}", name.display(),
+            if searched.is_empty() { "nowhere".to_string() } else { searched.join(", ") }))
+    }
+}
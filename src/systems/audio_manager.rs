@@ -0,0 +1,723 @@
+extern crate sdl2;
+
+use utilities::sound::{Sound, SoundStream, write_wav_header, write_wav_samples};
+
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom};
+use std::path::Path;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use sdl2::AudioSubsystem;
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+
+/// Sample rate the mixer produces, unless the output device asks for another.
+pub const OUTPUT_RATE: u32 = 44100;
+
+/// Number of frames decoded from a music stream at a time.
+const MUSIC_CHUNK_FRAMES: usize = 4096;
+
+/// Seconds of music decoded ahead of the mixer, covering any stalls of the game thread.
+const MUSIC_BUFFER_TIME: f32 = 0.5;
+
+/// Seconds taken for ducked music to return to full volume.
+const DUCK_RELEASE_TIME: f32 = 0.5;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(value: f32, frames: usize) -> Arc<Sound> {
+        Arc::new(Sound::new(vec![value; frames], 1, OUTPUT_RATE).expect("Should work"))
+    }
+
+    #[test]
+    fn pans_voices_between_channels() {
+        let mut mixer = Mixer::new(OUTPUT_RATE);
+        mixer.play(&tone(0.5, 4), 1.0, -1.0);
+
+        let mut output = [0.0; 8];
+        mixer.mix(&mut output);
+        assert!((output[0] - 0.5).abs() < 1e-5);
+        assert!(output[1].abs() < 1e-5);
+    }
+
+    #[test]
+    fn sums_voices_and_drops_finished_ones() {
+        let mut mixer = Mixer::new(OUTPUT_RATE);
+        mixer.play(&tone(0.25, 2), 1.0, 0.0);
+        mixer.play(&tone(0.25, 4), 1.0, 0.0);
+
+        let mut output = [0.0; 6];
+        mixer.mix(&mut output);
+        let centre = (0.25 * (PI / 4.0).cos()) * 2.0;
+        assert!((output[0] - centre).abs() < 1e-5);
+        assert_eq!(mixer.voice_count(), 1);
+    }
+
+    /// A stream that repeats one value for a fixed number of frames.
+    struct ConstantStream {
+        value: f32,
+        frames: usize,
+        position: usize,
+    }
+
+    impl SoundStream for ConstantStream {
+        fn channels(&self) -> usize { 1 }
+
+        fn sample_rate(&self) -> u32 { OUTPUT_RATE }
+
+        fn read(&mut self, output: &mut Vec<f32>, frames: usize) -> Result<usize, String> {
+            let frames = frames.min(self.frames - self.position);
+            output.extend(vec![self.value; frames]);
+            self.position += frames;
+            Ok(frames)
+        }
+
+        fn rewind(&mut self) -> Result<(), String> {
+            self.position = 0;
+            Ok(())
+        }
+    }
+
+    fn music(value: f32, frames: usize) -> Arc<Mutex<MusicBuffer>> {
+        let mut decoder = MusicDecoder::new(Box::new(ConstantStream { value: value, frames: frames, position: 0 }));
+        decoder.fill().expect("Should work");
+        decoder.buffer.clone()
+    }
+
+    /// A stream that fails on every read.
+    struct BrokenStream;
+
+    impl SoundStream for BrokenStream {
+        fn channels(&self) -> usize { 1 }
+
+        fn sample_rate(&self) -> u32 { OUTPUT_RATE }
+
+        fn read(&mut self, _output: &mut Vec<f32>, _frames: usize) -> Result<usize, String> {
+            Err("broken".to_string())
+        }
+
+        fn rewind(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn loops_music_streams() {
+        let mut mixer = Mixer::new(OUTPUT_RATE);
+        mixer.play_music(&music(0.5, 3), 1.0, 0.0);
+
+        let mut output = [0.0; 20];
+        mixer.mix(&mut output);
+        let centre = 0.5 * (PI / 4.0).cos();
+        assert!(output.iter().all(|sample| (sample - centre).abs() < 1e-5));
+    }
+
+    #[test]
+    fn crossfades_between_tracks() {
+        let mut mixer = Mixer::new(OUTPUT_RATE);
+        mixer.play_music(&music(0.5, 100), 1.0, 0.0);
+        mixer.play_music(&music(0.25, 100), 1.0, 4.0 / OUTPUT_RATE as f32);
+        assert_eq!(mixer.music_count(), 2);
+
+        let mut output = [0.0; 12];
+        mixer.mix(&mut output);
+        let centre = (PI / 4.0).cos();
+        assert!((output[0] - 0.5 * centre).abs() < 1e-5);
+        assert!((output[10] - 0.25 * centre).abs() < 1e-5);
+        assert_eq!(mixer.music_count(), 1);
+    }
+
+    #[test]
+    fn ducks_music_but_not_effects() {
+        let mut mixer = Mixer::new(OUTPUT_RATE);
+        mixer.play_music(&music(0.5, 100), 1.0, 0.0);
+        mixer.duck_music(0.5, 1.0);
+
+        let mut output = [0.0; 2];
+        mixer.mix(&mut output);
+        assert!((output[0] - 0.25 * (PI / 4.0).cos()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn waits_for_music_that_has_not_been_decoded_yet() {
+        let buffer = Arc::new(Mutex::new(MusicBuffer::new(1, OUTPUT_RATE)));
+        let mut mixer = Mixer::new(OUTPUT_RATE);
+        mixer.play_music(&buffer, 1.0, 0.0);
+
+        let mut output = [1.0; 4];
+        mixer.mix(&mut output);
+        assert!(output.iter().all(|sample| *sample == 0.0));
+        assert_eq!(mixer.music_count(), 1);
+    }
+
+    #[test]
+    fn stops_music_that_fails_to_decode() {
+        let mut audio = AudioManager::with_null();
+        audio.play_music(Box::new(BrokenStream), 1.0, 0.0);
+        audio.update(0.1);
+
+        assert_eq!(audio.mixer.lock().expect("Should work").music_count(), 0);
+        assert!(audio.decoders.is_empty());
+    }
+
+    #[test]
+    fn renders_elapsed_time_to_wav() {
+        let path = ::std::env::temp_dir().join(format!("breakout_audio_test_{}.wav", ::std::process::id()));
+        {
+            let mut audio = AudioManager::with_wav_file(&path).expect("Should work");
+            audio.play(&tone(0.5, OUTPUT_RATE as usize), 1.0, 0.0);
+            audio.update(0.5);
+            audio.update(0.25);
+        }
+
+        let sound = ::utilities::sound::read_sound_from_file(&path).expect("Should work");
+        assert_eq!(sound.channels(), 2);
+        assert_eq!(sound.frames(), (OUTPUT_RATE as f32 * 0.75) as usize);
+        ::std::fs::remove_file(&path).expect("Should work");
+    }
+}
+
+/// A sound that is currently playing.
+struct Voice {
+    sound: Arc<Sound>,
+    position: f64,     // current frame within the sound, fractional when resampling
+    step: f64,         // frames of the sound to advance per output frame
+    left: f32,         // per channel gain, combining volume and pan
+    right: f32,
+}
+
+/// Decoded music waiting to be mixed.
+///
+/// Filled by a `MusicDecoder` on the game thread, so the audio thread only ever copies samples
+/// out of it and never touches the disk.
+pub struct MusicBuffer {
+    samples: VecDeque<f32>,     // interleaved samples that have not been played yet
+    channels: usize,
+    sample_rate: u32,
+    is_finished: bool,          // no more samples will be added
+}
+
+impl MusicBuffer {
+    fn new(channels: usize, sample_rate: u32) -> Self {
+        MusicBuffer {
+            samples: VecDeque::new(),
+            channels: channels,
+            sample_rate: sample_rate,
+            is_finished: false,
+        }
+    }
+
+    fn frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    fn frame(&self, frame: usize) -> (f32, f32) {
+        let index = frame * self.channels;
+        if index + self.channels > self.samples.len() {
+            (0.0, 0.0)
+        } else if self.channels == 1 {
+            (self.samples[index], self.samples[index])
+        } else {
+            (self.samples[index], self.samples[index + 1])
+        }
+    }
+}
+
+/// Streams a music track from disk into a bounded `MusicBuffer`, looping it forever.
+struct MusicDecoder {
+    stream: Box<SoundStream>,
+    buffer: Arc<Mutex<MusicBuffer>>,
+    capacity: usize,    // frames to keep decoded ahead of the mixer
+    decoded: Vec<f32>,  // samples decoded outside the lock, before they are handed over
+}
+
+impl MusicDecoder {
+    fn new(stream: Box<SoundStream>) -> Self {
+        let buffer = MusicBuffer::new(stream.channels(), stream.sample_rate());
+        let capacity = (MUSIC_BUFFER_TIME * stream.sample_rate() as f32) as usize;
+        MusicDecoder {
+            stream: stream,
+            buffer: Arc::new(Mutex::new(buffer)),
+            capacity: capacity.max(2),
+            decoded: Vec::new(),
+        }
+    }
+
+    /// Tops the buffer up to its capacity.
+    ///
+    /// On failure the buffer is marked as finished, so the mixer fades the track out once it has
+    /// played what is left.
+    fn fill(&mut self) -> Result<(), String> {
+        let wanted = {
+            let buffer = self.buffer.lock().map_err(|_| "Music buffer lock poisoned".to_string())?;
+            if buffer.is_finished {
+                return Ok(());
+            }
+            self.capacity.saturating_sub(buffer.frames())
+        };
+
+        // decode without holding the lock, so the audio thread is never kept waiting on the disk
+        self.decoded.clear();
+        let decoded = self.decode(wanted);
+
+        let mut buffer = self.buffer.lock().map_err(|_| "Music buffer lock poisoned".to_string())?;
+        buffer.samples.extend(self.decoded.drain(..));
+        match decoded {
+            Ok(is_finished) => {
+                buffer.is_finished = is_finished;
+                Ok(())
+            }
+            Err(e) => {
+                buffer.is_finished = true;
+                Err(e)
+            }
+        }
+    }
+
+    /// Decodes up to `frames` frames, rewinding at the end of the stream.
+    ///
+    /// Returns true once the stream can not provide any more audio.
+    fn decode(&mut self, frames: usize) -> Result<bool, String> {
+        let channels = self.stream.channels();
+        let mut rewound = false;
+        while self.decoded.len() / channels < frames {
+            let chunk = (frames - self.decoded.len() / channels).min(MUSIC_CHUNK_FRAMES);
+            if self.stream.read(&mut self.decoded, chunk)? == 0 {
+                // an empty stream would otherwise rewind forever
+                if rewound {
+                    return Ok(true);
+                }
+                self.stream.rewind()?;
+                rewound = true;
+            } else {
+                rewound = false;
+            }
+        }
+        Ok(false)
+    }
+
+    /// True while the mixer may still want more of the track.
+    fn is_playing(&self) -> bool {
+        // the mixer drops its handle once the track has faded out or been stopped
+        Arc::strong_count(&self.buffer) > 1
+            && self.buffer.lock().map(|buffer| !buffer.is_finished).unwrap_or(false)
+    }
+}
+
+/// A music track being mixed from its decoded buffer, looping until it is faded out.
+struct MusicVoice {
+    buffer: Arc<Mutex<MusicBuffer>>,
+    position: f64,     // current frame within the buffer
+    step: f64,
+    volume: f32,       // current gain, moving towards the target while fading
+    target: f32,
+    fade_rate: f32,    // change in gain per output frame
+}
+
+impl MusicVoice {
+    fn is_faded_out(&self) -> bool {
+        self.target == 0.0 && self.volume == 0.0
+    }
+}
+
+/// Mixes any number of voices into interleaved stereo output.
+pub struct Mixer {
+    voices: Vec<Voice>,
+    music: Vec<MusicVoice>,
+    sample_rate: u32,
+    duck_gain: f32,    // extra gain applied to music, below 1 while ducked
+    duck_hold: usize,  // output frames left before ducked music starts to recover
+}
+
+impl Mixer {
+    pub fn new(sample_rate: u32) -> Self {
+        Mixer {
+            voices: Vec::new(),
+            music: Vec::new(),
+            sample_rate: sample_rate,
+            duck_gain: 1.0,
+            duck_hold: 0,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn voice_count(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Starts playing `sound`, with `pan` ranging from -1 (left) to 1 (right).
+    pub fn play(&mut self, sound: &Arc<Sound>, volume: f32, pan: f32) {
+        // constant power panning keeps the loudness steady across the stereo field
+        let angle = (pan.max(-1.0).min(1.0) + 1.0) * PI / 4.0;
+
+        self.voices.push(Voice {
+            sound: sound.clone(),
+            position: 0.0,
+            step: sound.sample_rate() as f64 / self.sample_rate as f64,
+            left: volume * angle.cos(),
+            right: volume * angle.sin(),
+        });
+    }
+
+    pub fn music_count(&self) -> usize {
+        self.music.len()
+    }
+
+    /// Starts mixing a music track from `buffer`, crossfading from any current track over `fade` seconds.
+    pub fn play_music(&mut self, buffer: &Arc<Mutex<MusicBuffer>>, volume: f32, fade: f32) {
+        self.stop_music(fade);
+
+        let sample_rate = match buffer.lock() {
+            Ok(buffer) => buffer.sample_rate,
+            Err(_) => return,
+        };
+        let fade_frames = fade * self.sample_rate as f32;
+        let step = sample_rate as f64 / self.sample_rate as f64;
+        self.music.push(MusicVoice {
+            buffer: buffer.clone(),
+            position: 0.0,
+            step: step,
+            volume: if fade_frames >= 1.0 { 0.0 } else { volume },
+            target: volume,
+            fade_rate: if fade_frames >= 1.0 { volume / fade_frames } else { 0.0 },
+        });
+    }
+
+    /// Fades every playing track out over `fade` seconds.
+    pub fn stop_music(&mut self, fade: f32) {
+        let fade_frames = fade * self.sample_rate as f32;
+        for track in self.music.iter_mut() {
+            track.target = 0.0;
+            if fade_frames >= 1.0 {
+                track.fade_rate = track.volume / fade_frames;
+            } else {
+                track.volume = 0.0;
+            }
+        }
+        self.music.retain(|track| !track.is_faded_out());
+    }
+
+    /// Drops the music to `level` for `duration` seconds, after which it recovers gradually.
+    pub fn duck_music(&mut self, level: f32, duration: f32) {
+        self.duck_gain = self.duck_gain.min(level.max(0.0));
+        self.duck_hold = self.duck_hold.max((duration * self.sample_rate as f32) as usize);
+    }
+
+    /// Fills `output` (interleaved left/right samples) with the next stretch of audio.
+    pub fn mix(&mut self, output: &mut [f32]) {
+        for sample in output.iter_mut() {
+            *sample = 0.0;
+        }
+
+        for voice in self.voices.iter_mut() {
+            for frame in output.chunks_mut(2) {
+                let index = voice.position as usize;
+                if index >= voice.sound.frames() {
+                    break;
+                }
+
+                // linearly interpolate between neighbouring frames
+                let fraction = (voice.position - index as f64) as f32;
+                let (left_a, right_a) = voice.sound.frame(index);
+                let (left_b, right_b) = voice.sound.frame(index + 1);
+                let left = left_a + (left_b - left_a) * fraction;
+                let right = right_a + (right_b - right_a) * fraction;
+
+                frame[0] += left * voice.left;
+                if frame.len() > 1 {
+                    frame[1] += right * voice.right;
+                }
+
+                voice.position += voice.step;
+            }
+        }
+
+        self.voices.retain(|voice| (voice.position as usize) < voice.sound.frames());
+
+        self.mix_music(output);
+
+        for sample in output.iter_mut() {
+            *sample = sample.max(-1.0).min(1.0);
+        }
+    }
+
+    fn mix_music(&mut self, output: &mut [f32]) {
+        let release = 1.0 / (DUCK_RELEASE_TIME * self.sample_rate as f32);
+        let (duck_gain, duck_hold) = (self.duck_gain, self.duck_hold);
+
+        for track in self.music.iter_mut() {
+            let (mut gain, mut hold) = (duck_gain, duck_hold);
+            let buffer = track.buffer.clone();
+            let mut buffer = match buffer.lock() {
+                Ok(buffer) => buffer,
+                Err(_) => { track.target = 0.0; track.volume = 0.0; continue }
+            };
+
+            for frame in output.chunks_mut(2) {
+                // drop frames that have already been played
+                let played = (track.position as usize).min(buffer.frames());
+                let channels = buffer.channels;
+                buffer.samples.drain(..played * channels);
+                track.position -= played as f64;
+
+                // the frames either side of the current position are needed to interpolate
+                if buffer.frames() >= 2 {
+                    let index = track.position as usize;
+                    let fraction = (track.position - index as f64) as f32;
+                    let (left_a, right_a) = buffer.frame(index);
+                    let (left_b, right_b) = buffer.frame(index + 1);
+                    let volume = track.volume * gain * (PI / 4.0).cos();
+
+                    frame[0] += (left_a + (left_b - left_a) * fraction) * volume;
+                    if frame.len() > 1 {
+                        frame[1] += (right_a + (right_b - right_a) * fraction) * volume;
+                    }
+                    track.position += track.step;
+                } else if buffer.is_finished {
+                    track.target = 0.0;
+                    track.volume = 0.0;
+                    break;
+                }
+                // otherwise the decoder has fallen behind, so this frame of the track is silent
+
+                // move towards the target gain
+                if track.volume < track.target {
+                    track.volume = (track.volume + track.fade_rate).min(track.target);
+                } else if track.volume > track.target {
+                    track.volume = (track.volume - track.fade_rate).max(track.target);
+                }
+
+                if hold > 0 {
+                    hold -= 1;
+                } else {
+                    gain = (gain + release).min(1.0);
+                }
+            }
+        }
+
+        // the duck envelope advances once per mix, however many tracks are playing
+        let frames = output.len() / 2;
+        let recovering = frames.saturating_sub(self.duck_hold);
+        self.duck_hold = self.duck_hold.saturating_sub(frames);
+        self.duck_gain = (self.duck_gain + release * recovering as f32).min(1.0);
+
+        self.music.retain(|track| !track.is_faded_out());
+    }
+}
+
+/// Destination for mixed audio.
+pub trait AudioBackend {
+    /// Called once per frame with the time elapsed since the last call.
+    ///
+    /// Backends that are not driven by a device clock mix `dt` seconds worth of audio here.
+    fn update(&mut self, mixer: &Arc<Mutex<Mixer>>, dt: f32);
+}
+
+struct MixerCallback {
+    mixer: Arc<Mutex<Mixer>>,
+}
+
+impl AudioCallback for MixerCallback {
+    type Channel = f32;
+
+    fn callback(&mut self, output: &mut [f32]) {
+        match self.mixer.lock() {
+            Ok(mut mixer) => mixer.mix(output),
+            Err(_) => for sample in output.iter_mut() { *sample = 0.0 },
+        }
+    }
+}
+
+/// Plays audio through an SDL2 audio device, which pulls from the mixer on its own thread.
+pub struct SdlBackend {
+    _device: AudioDevice<MixerCallback>,   // only held so the device keeps playing
+}
+
+impl AudioBackend for SdlBackend {
+    fn update(&mut self, _mixer: &Arc<Mutex<Mixer>>, _dt: f32) {}
+}
+
+/// Keeps track of elapsed time for backends that mix on the game thread.
+struct FrameClock {
+    pending: f64,   // fractional frames carried over between updates
+}
+
+impl FrameClock {
+    fn frames(&mut self, sample_rate: u32, dt: f32) -> usize {
+        self.pending += dt as f64 * sample_rate as f64;
+        let frames = self.pending.floor();
+        self.pending -= frames;
+        frames as usize
+    }
+}
+
+/// Mixes audio and throws it away, so voices still progress without a sound card.
+pub struct NullBackend {
+    clock: FrameClock,
+    buffer: Vec<f32>,
+}
+
+impl AudioBackend for NullBackend {
+    fn update(&mut self, mixer: &Arc<Mutex<Mixer>>, dt: f32) {
+        let mut mixer = mixer.lock().expect("| ERROR::AUDIO: Mixer lock poisoned");
+        let frames = self.clock.frames(mixer.sample_rate(), dt);
+        self.buffer.resize(frames * 2, 0.0);
+        mixer.mix(&mut self.buffer);
+    }
+}
+
+/// Renders everything that is played into a 16 bit stereo WAV file.
+pub struct WavFileBackend {
+    writer: BufWriter<File>,
+    clock: FrameClock,
+    buffer: Vec<f32>,
+    sample_rate: u32,
+    frames: u32,
+}
+
+impl WavFileBackend {
+    pub fn new<T : AsRef<Path>>(filename: &T, sample_rate: u32) -> Result<Self, String> {
+        let file = File::create(filename).map_err(|e| e.to_string())?;
+        let mut writer = BufWriter::new(file);
+        // the header is rewritten with the final length once rendering finishes
+        write_wav_header(&mut writer, sample_rate, 2, 0)?;
+
+        Ok(WavFileBackend {
+            writer: writer,
+            clock: FrameClock { pending: 0.0 },
+            buffer: Vec::new(),
+            sample_rate: sample_rate,
+            frames: 0,
+        })
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.writer.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+        write_wav_header(&mut self.writer, self.sample_rate, 2, self.frames)?;
+        self.writer.seek(SeekFrom::End(0)).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+impl AudioBackend for WavFileBackend {
+    fn update(&mut self, mixer: &Arc<Mutex<Mixer>>, dt: f32) {
+        let mut mixer = mixer.lock().expect("| ERROR::AUDIO: Mixer lock poisoned");
+        let frames = self.clock.frames(mixer.sample_rate(), dt);
+        self.buffer.resize(frames * 2, 0.0);
+        mixer.mix(&mut self.buffer);
+
+        match write_wav_samples(&mut self.writer, &self.buffer) {
+            Ok(()) => self.frames += frames as u32,
+            Err(e) => println!("| ERROR::AUDIO: Could not write audio - {}", e),
+        }
+    }
+}
+
+impl Drop for WavFileBackend {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            println!("| ERROR::AUDIO: Could not finish WAV file - {}", e);
+        }
+    }
+}
+
+pub struct AudioManager {
+    mixer: Arc<Mutex<Mixer>>,
+    backend: Box<AudioBackend>,
+    decoders: Vec<MusicDecoder>,   // music being decoded on the game thread for the mixer
+}
+
+impl AudioManager {
+    /// Plays through the default SDL2 audio device.
+    pub fn with_sdl(audio: &AudioSubsystem) -> Result<Self, String> {
+        let desired = AudioSpecDesired {
+            freq: Some(OUTPUT_RATE as i32),
+            channels: Some(2),
+            samples: Some(1024),
+        };
+
+        let mixer = Arc::new(Mutex::new(Mixer::new(OUTPUT_RATE)));
+        let device = {
+            let mixer = mixer.clone();
+            audio.open_playback(None, &desired, move |spec| {
+                // mix at whatever rate the device actually gave us
+                if let Ok(mut mixer) = mixer.lock() {
+                    mixer.sample_rate = spec.freq as u32;
+                }
+                MixerCallback { mixer: mixer }
+            })?
+        };
+        device.resume();
+
+        Ok(AudioManager {
+            mixer: mixer,
+            backend: Box::new(SdlBackend { _device: device }),
+            decoders: Vec::new(),
+        })
+    }
+
+    pub fn with_null() -> Self {
+        AudioManager {
+            mixer: Arc::new(Mutex::new(Mixer::new(OUTPUT_RATE))),
+            backend: Box::new(NullBackend { clock: FrameClock { pending: 0.0 }, buffer: Vec::new() }),
+            decoders: Vec::new(),
+        }
+    }
+
+    pub fn with_wav_file<T : AsRef<Path>>(filename: &T) -> Result<Self, String> {
+        Ok(AudioManager {
+            mixer: Arc::new(Mutex::new(Mixer::new(OUTPUT_RATE))),
+            backend: Box::new(WavFileBackend::new(filename, OUTPUT_RATE)?),
+            decoders: Vec::new(),
+        })
+    }
+
+    pub fn play(&mut self, sound: &Arc<Sound>, volume: f32, pan: f32) {
+        if let Ok(mut mixer) = self.mixer.lock() {
+            mixer.play(sound, volume, pan);
+        }
+    }
+
+    /// Starts streaming a looping music track, crossfading from any current track over `fade` seconds.
+    pub fn play_music(&mut self, stream: Box<SoundStream>, volume: f32, fade: f32) {
+        // decode the start of the track up front, so it does not begin with a gap
+        let mut decoder = MusicDecoder::new(stream);
+        if let Err(e) = decoder.fill() {
+            println!("| ERROR::AUDIO: Could not stream music - {}", e);
+        }
+
+        if let Ok(mut mixer) = self.mixer.lock() {
+            mixer.play_music(&decoder.buffer, volume, fade);
+        }
+        self.decoders.push(decoder);
+    }
+
+    pub fn stop_music(&mut self, fade: f32) {
+        if let Ok(mut mixer) = self.mixer.lock() {
+            mixer.stop_music(fade);
+        }
+    }
+
+    pub fn duck_music(&mut self, level: f32, duration: f32) {
+        if let Ok(mut mixer) = self.mixer.lock() {
+            mixer.duck_music(level, duration);
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.decoders.retain(|decoder| decoder.is_playing());
+        for decoder in self.decoders.iter_mut() {
+            if let Err(e) = decoder.fill() {
+                println!("| ERROR::AUDIO: Could not stream music - {}", e);
+            }
+        }
+
+        self.backend.update(&self.mixer, dt);
+    }
+}
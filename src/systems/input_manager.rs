@@ -2,49 +2,704 @@ extern crate gl;
 extern crate nalgebra;
 extern crate sdl2;
 
-use systems::resource_manager::ResourceManager;
-use utilities::sprite_renderer::SpriteRenderer;
-use utilities::texture::Texture;
-use utilities::game_level::GameLevel;
-use objects::player::Player;
+use utilities::viewport::Viewport;
 
-use std::cell::RefCell;
-use std::ptr::null;
-use std::rc::Rc;
+use std::collections::{HashMap, HashSet};
 
 use gl::types::GLfloat;
-use nalgebra::base::{Matrix4, Vector2, Vector3};
-use sdl2::event::{Event};
-use sdl2::keyboard::Keycode;
+use nalgebra::base::{Matrix4, Vector2, Vector4};
+use sdl2::GameControllerSubsystem;
+use sdl2::controller::{Axis as ControllerAxis, Button, GameController};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
 
-#[derive(Debug)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sdl2::mouse::MouseState;
+
+    fn key_down(keycode: Keycode, repeat: bool) -> Event {
+        Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(keycode), scancode: None, keymod: Mod::empty(), repeat: repeat }
+    }
+
+    fn key_up(keycode: Keycode) -> Event {
+        Event::KeyUp { timestamp: 0, window_id: 0, keycode: Some(keycode), scancode: None, keymod: Mod::empty(), repeat: false }
+    }
+
+    #[test]
+    fn tracks_pressed_held_and_released() {
+        let mut input = InputState::new(KeyBindings::new());
+
+        input.update(&[key_down(Keycode::Left, false)]);
+        assert!(input.is_pressed(Input::LEFT) && input.is_held(Input::LEFT));
+
+        input.update(&[key_down(Keycode::Left, true)]);
+        assert!(!input.is_pressed(Input::LEFT) && input.is_held(Input::LEFT));
+
+        input.update(&[key_up(Keycode::Left)]);
+        assert!(input.is_released(Input::LEFT) && !input.is_held(Input::LEFT));
+
+        input.update(&[]);
+        assert!(!input.is_released(Input::LEFT));
+    }
+
+    #[test]
+    fn holds_actions_until_every_key_is_released() {
+        let mut input = InputState::new(KeyBindings::new());
+        input.update(&[key_down(Keycode::Left, false), key_down(Keycode::A, false)]);
+        input.update(&[key_up(Keycode::A)]);
+        assert!(input.is_held(Input::LEFT) && !input.is_released(Input::LEFT));
+
+        input.update(&[key_up(Keycode::Left)]);
+        assert!(input.is_released(Input::LEFT));
+    }
+
+    fn button(button: Button, down: bool) -> Event {
+        if down {
+            Event::ControllerButtonDown { timestamp: 0, which: 1, button: button }
+        } else {
+            Event::ControllerButtonUp { timestamp: 0, which: 1, button: button }
+        }
+    }
+
+    fn stick_x(value: i16) -> Event {
+        Event::ControllerAxisMotion { timestamp: 0, which: 1, axis: ControllerAxis::LeftX, value: value }
+    }
+
+    #[test]
+    fn maps_controller_buttons_to_actions() {
+        let mut input = InputState::new(KeyBindings::new());
+        input.update(&[button(Button::A, true)]);
+        assert!(input.is_pressed(Input::ACTION0) && input.is_pressed(Input::LAUNCH));
+
+        input.update(&[button(Button::A, false), button(Button::Start, true)]);
+        assert!(input.is_released(Input::LAUNCH) && input.is_pressed(Input::PAUSE));
+
+        // a removed controller releases everything it was holding
+        input.update(&[Event::ControllerDeviceRemoved { timestamp: 0, which: 1 }]);
+        assert!(input.is_released(Input::PAUSE));
+    }
+
+    #[test]
+    fn reads_proportional_stick_movement() {
+        let mut input = InputState::new(KeyBindings::new());
+        input.update(&[stick_x(3000)]);
+        assert_eq!(input.axis(InputAxis::HORIZONTAL), 0.0);
+
+        input.update(&[stick_x(-16384)]);
+        let value = input.axis(InputAxis::HORIZONTAL);
+        assert!(value < -0.3 && value > -0.5);
+        assert!(!input.is_held(Input::LEFT));
+
+        input.update(&[stick_x(-32767)]);
+        assert!(input.is_pressed(Input::LEFT));
+
+        // keys override the stick
+        input.update(&[key_down(Keycode::Right, false)]);
+        assert_eq!(input.axis(InputAxis::HORIZONTAL), 1.0);
+    }
+
+    #[test]
+    fn maps_window_positions_through_the_projection() {
+        let projection = Matrix4::new_orthographic(0.0, 700.0, 0.0, 900.0, -1.0, 1.0);
+        let world = screen_to_world(&projection, &Viewport::full(700, 900), &Vector2::new(0.25, 0.25));
+        assert!((world.x - 175.0).abs() < 1e-3);
+        // window coordinates point down, world coordinates point up
+        assert!((world.y - 675.0).abs() < 1e-3);
+
+        // a window twice as wide has bars either side of the game
+        let world = screen_to_world(&projection, &Viewport::letterbox(2800, 1800, 700.0 / 900.0), &Vector2::new(0.25, 0.25));
+        assert!((world.x - 0.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn records_mouse_movement_in_world_units() {
+        let motion = Event::MouseMotion { timestamp: 0, window_id: 0, which: 0, mousestate: MouseState::from_sdl_state(0), x: 0, y: 0, xrel: 100, yrel: 0 };
+
+        let mut input = InputState::new(KeyBindings::new());
+        input.set_window_size(1600, 600);
+        // the game fills the middle half of the window, so 100 window pixels are 100 world units
+        input.set_view(&Matrix4::new_orthographic(0.0, 800.0, 0.0, 600.0, -1.0, 1.0), &Viewport::letterbox(1600, 600, 800.0 / 600.0));
+        input.update(&[motion.clone()]);
+        assert!((input.mouse_motion() - 100.0).abs() < 1e-3);
+
+        // the same recorded frame moves the paddle as far whatever the window size
+        input.set_window_size(3200, 1200);
+        input.set_view(&Matrix4::new_orthographic(0.0, 800.0, 0.0, 600.0, -1.0, 1.0), &Viewport::letterbox(3200, 1200, 800.0 / 600.0));
+        input.update(&[motion]);
+        assert!((input.mouse_motion() - 50.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn tracks_touches_and_taps() {
+        let finger = |down: bool, id: i64, x: f32| if down {
+            Event::FingerDown { timestamp: 0, touch_id: 0, finger_id: id, x: x, y: 0.5, dx: 0.0, dy: 0.0, pressure: 1.0 }
+        } else {
+            Event::FingerUp { timestamp: 0, touch_id: 0, finger_id: id, x: x, y: 0.5, dx: 0.0, dy: 0.0, pressure: 1.0 }
+        };
+
+        let mut input = InputState::new(KeyBindings::new());
+        input.set_view(&Matrix4::new_orthographic(0.0, 800.0, 0.0, 600.0, -1.0, 1.0), &Viewport::full(800, 600));
+        input.update(&[finger(true, 3, 0.25), finger(true, 4, 0.75)]);
+        assert!(input.is_pointer_pressed());
+        assert_eq!(input.touch_position(), Some(Vector2::new(200.0, 300.0)));
+
+        input.update(&[finger(false, 4, 0.75)]);
+        assert!(!input.is_pointer_pressed());
+        assert_eq!(input.touch_position(), Some(Vector2::new(200.0, 300.0)));
+
+        input.update(&[finger(false, 3, 0.25)]);
+        assert_eq!(input.touch_position(), None);
+    }
+
+    #[test]
+    fn follows_rebound_keys() {
+        let mut bindings = KeyBindings::new();
+        bindings.set(Input::LAUNCH, vec![Keycode::K]);
+        let mut input = InputState::new(bindings);
+
+        input.update(&[key_down(Keycode::Space, false), key_down(Keycode::K, false)]);
+        assert!(input.is_pressed(Input::LAUNCH));
+        input.update(&[key_up(Keycode::K)]);
+        assert!(!input.is_held(Input::LAUNCH));
+    }
+
+    #[test]
+    fn toggles_keys_but_keeps_one_binding() {
+        let mut bindings = KeyBindings::new();
+        bindings.set(Input::PAUSE, vec![Keycode::P]);
+        bindings.toggle(Input::PAUSE, Keycode::F1);
+        assert_eq!(bindings.keys(Input::PAUSE), &[Keycode::P, Keycode::F1]);
+
+        bindings.toggle(Input::PAUSE, Keycode::P);
+        bindings.toggle(Input::PAUSE, Keycode::F1);
+        assert_eq!(bindings.keys(Input::PAUSE), &[Keycode::F1]);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Input {
-   UP,
+    UP,
     DOWN,
     LEFT,
     RIGHT,
-    ACTION0
-}
-
-pub fn parse_input(elements: &Vec<Event>) -> Vec<Input> {
-    elements.iter().filter_map(|elem|
-        if let &Event::KeyDown {
-            keycode: Some(ref keycode),
-            ..
-        } = elem {
-            println!("keycode: {:?}", keycode);
-           match keycode {
-               Keycode::D => Some(Input::RIGHT),
-               Keycode::A => Some(Input::LEFT),
-               Keycode::S => Some(Input::DOWN),
-               Keycode::W => Some(Input::UP),
-               Keycode::Right => Some(Input::RIGHT),
-               Keycode::Left => Some(Input::LEFT),
-               Keycode::Up   => Some(Input::UP),
-               Keycode::Down => Some(Input::DOWN),
-               Keycode::J => Some(Input::ACTION0),
-               _ => None
-           }
-        } else {None}).collect()
+    ACTION0,
+    LAUNCH,
+    PAUSE,
+    BACK,
+    SCREENSHOT,
+}
+
+pub const INPUT_COUNT: usize = 9;
+
+/// Every action, in the order they are listed on the options screen.
+pub const INPUTS: [Input; INPUT_COUNT] = [
+    Input::UP,
+    Input::DOWN,
+    Input::LEFT,
+    Input::RIGHT,
+    Input::ACTION0,
+    Input::LAUNCH,
+    Input::PAUSE,
+    Input::BACK,
+    Input::SCREENSHOT,
+];
+
+impl Input {
+    /// Name used for the action in the bindings file.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Input::UP => "up",
+            Input::DOWN => "down",
+            Input::LEFT => "left",
+            Input::RIGHT => "right",
+            Input::ACTION0 => "action",
+            Input::LAUNCH => "launch",
+            Input::PAUSE => "pause",
+            Input::BACK => "back",
+            Input::SCREENSHOT => "screenshot",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Input> {
+        INPUTS.iter().find(|input| input.name() == name).cloned()
+    }
+}
+
+pub fn input_index(input: Input) -> usize {
+    match input {
+        Input::UP => 0,
+        Input::DOWN => 1,
+        Input::LEFT => 2,
+        Input::RIGHT => 3,
+        Input::ACTION0 => 4,
+        Input::LAUNCH => 5,
+        Input::PAUSE => 6,
+        Input::BACK => 7,
+        Input::SCREENSHOT => 8,
+    }
+}
+
+/// Ways of steering the paddle.
+///
+/// Keys and controllers keep working for everything else, whatever the scheme.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlScheme {
+    KEYBOARD,   // keys, d-pad and sticks
+    MOUSE,      // horizontal mouse movement, with the mouse captured
+    TOUCH,      // the paddle follows the finger
+}
+
+impl ControlScheme {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ControlScheme::KEYBOARD => "keyboard",
+            ControlScheme::MOUSE => "mouse",
+            ControlScheme::TOUCH => "touch",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ControlScheme> {
+        match name {
+            "keyboard" => Some(ControlScheme::KEYBOARD),
+            "mouse" => Some(ControlScheme::MOUSE),
+            "touch" => Some(ControlScheme::TOUCH),
+            _ => None,
+        }
+    }
+
+    /// The scheme after this one, for cycling through them on the options screen.
+    pub fn next(&self) -> ControlScheme {
+        match *self {
+            ControlScheme::KEYBOARD => ControlScheme::MOUSE,
+            ControlScheme::MOUSE => ControlScheme::TOUCH,
+            ControlScheme::TOUCH => ControlScheme::KEYBOARD,
+        }
+    }
+}
+
+/// The keys bound to each action.
+///
+/// An action can have several keys, and a key can trigger several actions - the default
+/// bindings use J both to confirm in menus and to launch the ball.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    keys: Vec<Vec<Keycode>>,   // indexed by action
+    scheme: ControlScheme,     // how the paddle is steered
+}
+
+impl KeyBindings {
+    /// Creates the default bindings, which cover both QWERTY (WASD) and AZERTY (ZQSD) layouts.
+    pub fn new() -> Self {
+        let mut bindings = KeyBindings {
+            keys: vec![Vec::new(); INPUT_COUNT],
+            scheme: ControlScheme::KEYBOARD,
+        };
+        bindings.set(Input::UP, vec![Keycode::W, Keycode::Z, Keycode::Up]);
+        bindings.set(Input::DOWN, vec![Keycode::S, Keycode::Down]);
+        bindings.set(Input::LEFT, vec![Keycode::A, Keycode::Q, Keycode::Left]);
+        bindings.set(Input::RIGHT, vec![Keycode::D, Keycode::Right]);
+        bindings.set(Input::ACTION0, vec![Keycode::J, Keycode::Return]);
+        bindings.set(Input::LAUNCH, vec![Keycode::J, Keycode::Space]);
+        bindings.set(Input::PAUSE, vec![Keycode::P]);
+        bindings.set(Input::BACK, vec![Keycode::Escape, Keycode::Backspace]);
+        bindings.set(Input::SCREENSHOT, vec![Keycode::F12]);
+        bindings
+    }
+
+    pub fn keys(&self, input: Input) -> &[Keycode] {
+        &self.keys[input_index(input)]
+    }
+
+    pub fn set(&mut self, input: Input, keys: Vec<Keycode>) {
+        self.keys[input_index(input)] = keys;
+    }
+
+    pub fn scheme(&self) -> ControlScheme {
+        self.scheme
+    }
+
+    pub fn set_scheme(&mut self, scheme: ControlScheme) {
+        self.scheme = scheme;
+    }
+
+    /// Adds `keycode` to the action, or removes it if it is already bound.
+    ///
+    /// The last key of an action is never removed, so every action stays reachable.
+    pub fn toggle(&mut self, input: Input, keycode: Keycode) {
+        let keys = &mut self.keys[input_index(input)];
+        match keys.iter().position(|key| *key == keycode) {
+            Some(index) => if keys.len() > 1 { keys.remove(index); },
+            None => keys.push(keycode),
+        }
+    }
+}
+
+/// Continuous inputs, ranging from -1 to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputAxis {
+    HORIZONTAL,
+}
+
+/// Stick positions closer to the centre than this are treated as centred.
+pub const STICK_DEAD_ZONE: f32 = 0.2;
+
+/// How far a stick has to be pushed before it counts as holding a direction, for navigating menus.
+pub const STICK_DIGITAL_THRESHOLD: f32 = 0.5;
+
+/// Maps a controller button to the actions it triggers.
+pub fn button_to_inputs(button: Button) -> &'static [Input] {
+    match button {
+        Button::DPadUp => &[Input::UP],
+        Button::DPadDown => &[Input::DOWN],
+        Button::DPadLeft => &[Input::LEFT],
+        Button::DPadRight => &[Input::RIGHT],
+        Button::A => &[Input::ACTION0, Input::LAUNCH],
+        Button::B | Button::Back => &[Input::BACK],
+        Button::Start => &[Input::PAUSE],
+        _ => &[],
+    }
+}
+
+/// Converts a raw axis reading to the range [-1, 1], applying the dead zone.
+fn normalize_axis(value: i16) -> f32 {
+    let value = (value as f32 / 32767.0).max(-1.0).min(1.0);
+    if value.abs() < STICK_DEAD_ZONE {
+        0.0
+    } else {
+        value.signum() * (value.abs() - STICK_DEAD_ZONE) / (1.0 - STICK_DEAD_ZONE)
+    }
+}
+
+/// Mouse events SDL synthesizes from touches use this device id.
+const TOUCH_MOUSE_ID: u32 = 0xFFFFFFFF;
+
+/// Converts a position in the window, normalized to [0, 1] with y pointing down, to world
+/// coordinates by inverting `projection` within the part of the window covered by `viewport`.
+pub fn screen_to_world(projection: &Matrix4<GLfloat>, viewport: &Viewport, point: &Vector2<GLfloat>) -> Vector2<GLfloat> {
+    let point = viewport.window_to_viewport(point);
+    let ndc = Vector4::new(point.x * 2.0 - 1.0, 1.0 - point.y * 2.0, 0.0, 1.0);
+    let world = projection.try_inverse().unwrap_or(Matrix4::identity()) * ndc;
+    Vector2::new(world.x, world.y)
+}
+
+/// Everything the game reads from the input during one frame.
+///
+/// This is what replays record, so that playing one back does not depend on key bindings or
+/// window size - pointer input is kept in world units for the same reason.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputFrame {
+    pub held: [bool; INPUT_COUNT],
+    pub pressed: [bool; INPUT_COUNT],    // actions that went down this frame
+    pub released: [bool; INPUT_COUNT],   // actions that went up this frame
+    pub horizontal: f32,                 // value of `InputAxis::HORIZONTAL`
+    pub mouse_motion: f32,               // horizontal mouse movement, in world units
+    pub touch: Option<Vector2<f32>>,     // world position of the finger steering the paddle
+    pub pointer_pressed: bool,           // clicked or tapped this frame
+    pub pressed_keys: Vec<Keycode>,      // keys that went down this frame, in order
+}
+
+impl InputFrame {
+    pub fn new() -> Self {
+        InputFrame {
+            held: [false; INPUT_COUNT],
+            pressed: [false; INPUT_COUNT],
+            released: [false; INPUT_COUNT],
+            horizontal: 0.0,
+            mouse_motion: 0.0,
+            touch: None,
+            pointer_pressed: false,
+            pressed_keys: Vec::new(),
+        }
+    }
+}
+
+/// Tracks which actions are held down, built up from the key and controller events of each frame.
+///
+/// Several keys and buttons can map to the same action - the action stays held until all of them
+/// are released.
+pub struct InputState {
+    bindings: KeyBindings,
+    keys: HashSet<Keycode>,             // keys currently held down
+    buttons: HashSet<(i32, Button)>,    // controller buttons held down, by controller instance id
+    sticks: HashMap<i32, (f32, f32)>,   // left stick position of each controller
+    touch: Option<(i64, Vector2<f32>)>, // the first finger still on the screen and its normalized position
+    window_size: (f32, f32),
+    projection: Matrix4<GLfloat>,       // maps pointer positions into the world, with `viewport`
+    viewport: Viewport,
+    frame: InputFrame,
+}
+
+impl InputState {
+    pub fn new(bindings: KeyBindings) -> Self {
+        InputState {
+            bindings: bindings,
+            keys: HashSet::new(),
+            buttons: HashSet::new(),
+            sticks: HashMap::new(),
+            touch: None,
+            window_size: (1.0, 1.0),
+            projection: Matrix4::identity(),
+            viewport: Viewport::full(1, 1),
+            frame: InputFrame::new(),
+        }
+    }
+
+    /// Sets the window size used to normalize mouse movement, which is kept up to date by resize events.
+    pub fn set_window_size(&mut self, width: u32, height: u32) {
+        self.window_size = (width.max(1) as f32, height.max(1) as f32);
+    }
+
+    /// Sets the projection and the part of the window the game is drawn in, which pointer input
+    /// is mapped through into world units.
+    pub fn set_view(&mut self, projection: &Matrix4<GLfloat>, viewport: &Viewport) {
+        self.projection = *projection;
+        self.viewport = *viewport;
+    }
+
+    fn to_world(&self, point: &Vector2<GLfloat>) -> Vector2<GLfloat> {
+        screen_to_world(&self.projection, &self.viewport, point)
+    }
+
+    pub fn bindings(&self) -> &KeyBindings {
+        &self.bindings
+    }
+
+    pub fn bindings_mut(&mut self) -> &mut KeyBindings {
+        &mut self.bindings
+    }
+
+    pub fn frame(&self) -> &InputFrame {
+        &self.frame
+    }
+
+    /// Replaces this frame's input with a recorded one, ignoring the live devices.
+    pub fn set_frame(&mut self, frame: InputFrame) {
+        self.frame = frame;
+    }
+
+    /// Starts a new frame, forgetting this frame's presses and releases and applying `events`.
+    pub fn update(&mut self, events: &[Event]) {
+        let mut frame = InputFrame::new();
+
+        for event in events {
+            let before = self.live_held();
+
+            match *event {
+                // key repeats are ignored, the key is already held
+                Event::KeyDown { keycode: Some(keycode), repeat: false, .. } => {
+                    self.keys.insert(keycode);
+                    frame.pressed_keys.push(keycode);
+                }
+                Event::KeyUp { keycode: Some(keycode), .. } => {
+                    self.keys.remove(&keycode);
+                }
+                Event::ControllerButtonDown { which, button, .. } => {
+                    self.buttons.insert((which, button));
+                }
+                Event::ControllerButtonUp { which, button, .. } => {
+                    self.buttons.remove(&(which, button));
+                }
+                Event::ControllerAxisMotion { which, axis, value, .. } => {
+                    let stick = self.sticks.entry(which).or_insert((0.0, 0.0));
+                    match axis {
+                        ControllerAxis::LeftX => stick.0 = normalize_axis(value),
+                        // SDL reports down as positive
+                        ControllerAxis::LeftY => stick.1 = -normalize_axis(value),
+                        _ => (),
+                    }
+                }
+                Event::ControllerDeviceRemoved { which, .. } => {
+                    self.buttons.retain(|&(id, _)| id != which);
+                    self.sticks.remove(&which);
+                }
+                // relative movement keeps coming while the mouse is captured, unlike the position
+                Event::MouseMotion { which, xrel, .. } if which != TOUCH_MOUSE_ID => {
+                    let moved = self.to_world(&Vector2::new(xrel as f32 / self.window_size.0, 0.0));
+                    frame.mouse_motion += moved.x - self.to_world(&Vector2::new(0.0, 0.0)).x;
+                }
+                Event::MouseButtonDown { which, mouse_btn: MouseButton::Left, .. } if which != TOUCH_MOUSE_ID => {
+                    frame.pointer_pressed = true;
+                }
+                Event::FingerDown { finger_id, x, y, .. } => {
+                    if self.touch.is_none() {
+                        self.touch = Some((finger_id, Vector2::new(x, y)));
+                        frame.pointer_pressed = true;
+                    }
+                }
+                Event::FingerMotion { finger_id, x, y, .. } => {
+                    if let Some((id, ref mut position)) = self.touch {
+                        if id == finger_id {
+                            *position = Vector2::new(x, y);
+                        }
+                    }
+                }
+                Event::FingerUp { finger_id, .. } => {
+                    if self.touch.map(|(id, _)| id == finger_id).unwrap_or(false) {
+                        self.touch = None;
+                    }
+                }
+                Event::Window { win_event: WindowEvent::SizeChanged(width, height), .. } => {
+                    self.set_window_size(width as u32, height as u32);
+                }
+                // key releases are not delivered while the window is unfocused
+                Event::Window { win_event: WindowEvent::FocusLost, .. } => {
+                    self.keys.clear();
+                }
+                _ => (),
+            }
+
+            let after = self.live_held();
+            for i in 0..INPUT_COUNT {
+                frame.pressed[i] |= after[i] && !before[i];
+                frame.released[i] |= before[i] && !after[i];
+            }
+        }
+
+        frame.held = self.live_held();
+        frame.horizontal = self.live_horizontal();
+        frame.touch = self.touch.map(|(_, position)| self.to_world(&position));
+        self.frame = frame;
+    }
+
+    fn live_held(&self) -> [bool; INPUT_COUNT] {
+        let mut held = [false; INPUT_COUNT];
+        for &input in INPUTS.iter() {
+            held[input_index(input)] = self.is_live_held(input);
+        }
+        held
+    }
+
+    /// Held through a key or button, as opposed to a stick.
+    fn is_held_digitally(&self, input: Input) -> bool {
+        self.bindings.keys(input).iter().any(|keycode| self.keys.contains(keycode))
+            || self.buttons.iter().any(|&(_, button)| button_to_inputs(button).contains(&input))
+    }
+
+    fn is_live_held(&self, input: Input) -> bool {
+        if self.is_held_digitally(input) {
+            return true;
+        }
+        let (x, y) = self.stick();
+        match input {
+            Input::LEFT => x <= -STICK_DIGITAL_THRESHOLD,
+            Input::RIGHT => x >= STICK_DIGITAL_THRESHOLD,
+            Input::DOWN => y <= -STICK_DIGITAL_THRESHOLD,
+            Input::UP => y >= STICK_DIGITAL_THRESHOLD,
+            _ => false,
+        }
+    }
+
+    /// Keys and the d-pad give full deflection, and take priority over the sticks.
+    fn live_horizontal(&self) -> f32 {
+        let digital = self.is_held_digitally(Input::RIGHT) as i32 - self.is_held_digitally(Input::LEFT) as i32;
+        if digital != 0 {
+            digital as f32
+        } else {
+            self.stick().0
+        }
+    }
+
+    /// The stick position pushed furthest from the centre, across every controller.
+    fn stick(&self) -> (f32, f32) {
+        let furthest = |a: f32, b: f32| if b.abs() > a.abs() { b } else { a };
+        self.sticks.values()
+            .fold((0.0, 0.0), |(x, y), &(stick_x, stick_y)| (furthest(x, stick_x), furthest(y, stick_y)))
+    }
+
+    /// Keys that went down this frame, whatever they are bound to.
+    pub fn pressed_keys(&self) -> &[Keycode] {
+        &self.frame.pressed_keys
+    }
+
+    /// True only on the frame the action went down.
+    pub fn is_pressed(&self, input: Input) -> bool {
+        self.frame.pressed[input_index(input)]
+    }
+
+    /// True for as long as any key or button bound to the action is down, or a stick is
+    /// pushed far enough in its direction.
+    pub fn is_held(&self, input: Input) -> bool {
+        self.frame.held[input_index(input)]
+    }
+
+    /// True only on the frame the action went up.
+    pub fn is_released(&self, input: Input) -> bool {
+        self.frame.released[input_index(input)]
+    }
+
+    /// Horizontal mouse movement this frame, in world units.
+    pub fn mouse_motion(&self) -> f32 {
+        self.frame.mouse_motion
+    }
+
+    /// World position of the finger steering the paddle, while one is down.
+    pub fn touch_position(&self) -> Option<Vector2<f32>> {
+        self.frame.touch
+    }
+
+    /// True on the frame the left mouse button was clicked or the screen was first touched.
+    pub fn is_pointer_pressed(&self) -> bool {
+        self.frame.pointer_pressed
+    }
+
+    /// Current value of an analog input.
+    pub fn axis(&self, axis: InputAxis) -> f32 {
+        match axis {
+            InputAxis::HORIZONTAL => self.frame.horizontal,
+        }
+    }
+}
+
+/// Keeps every connected game controller open, so their events are delivered.
+///
+/// Controllers are opened and closed as they are plugged in and removed while the game runs.
+pub struct ControllerManager {
+    subsystem: GameControllerSubsystem,
+    controllers: Vec<GameController>,
+}
+
+impl ControllerManager {
+    pub fn new(subsystem: GameControllerSubsystem) -> Self {
+        let mut manager = ControllerManager {
+            subsystem: subsystem,
+            controllers: Vec::new(),
+        };
+
+        let count = manager.subsystem.num_joysticks().unwrap_or(0);
+        for index in 0..count {
+            manager.open(index);
+        }
+        manager
+    }
+
+    fn open(&mut self, joystick_index: u32) {
+        if !self.subsystem.is_game_controller(joystick_index) {
+            return;
+        }
+        match self.subsystem.open(joystick_index) {
+            Ok(controller) => {
+                // devices present at startup are also announced with an added event
+                if self.controllers.iter().all(|open| open.instance_id() != controller.instance_id()) {
+                    println!("| INFO::INPUT: Connected controller {}", controller.name());
+                    self.controllers.push(controller);
+                }
+            }
+            Err(e) => println!("| ERROR::INPUT: Could not open controller {} - {:?}", joystick_index, e),
+        }
+    }
 
+    pub fn update(&mut self, events: &[Event]) {
+        for event in events {
+            match *event {
+                Event::ControllerDeviceAdded { which, .. } => self.open(which),
+                Event::ControllerDeviceRemoved { which, .. } => {
+                    self.controllers.retain(|controller| controller.instance_id() != which);
+                }
+                _ => (),
+            }
+        }
+    }
 }
@@ -1,2 +1,5 @@
+pub mod asset_locator;
+pub mod audio_manager;
 pub mod input_manager;
+pub mod particle_manager;
 pub mod resource_manager;
@@ -0,0 +1,375 @@
+extern crate gl;
+extern crate nalgebra;
+
+use objects::game_object::GameObject;
+use utilities::random::Random;
+use utilities::shader::Shader;
+use utilities::sprite_renderer::buffer_data;
+use utilities::texture::Texture;
+
+use std::cell::RefCell;
+use std::mem::size_of;
+use std::os::raw::c_void;
+use std::ptr::null;
+use std::rc::Rc;
+
+use gl::types::{GLfloat, GLint, GLsizei, GLsizeiptr, GLuint};
+use nalgebra::base::{Vector2, Vector3, Vector4};
+
+/// Number of floats uploaded per particle instance - centre, size, padding and colour.
+const INSTANCE_FLOATS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    position: Vector2<GLfloat>,
+    velocity: Vector2<GLfloat>,
+    color: Vector4<GLfloat>,
+    size: GLfloat,
+    life: GLfloat,
+    max_life: GLfloat,
+}
+
+impl Particle {
+    fn dead() -> Self {
+        Particle {
+            position: Vector2::new(0.0, 0.0),
+            velocity: Vector2::new(0.0, 0.0),
+            color: Vector4::new(1.0, 1.0, 1.0, 1.0),
+            size: 0.0,
+            life: 0.0,
+            max_life: 1.0,
+        }
+    }
+}
+
+/// Fixed size pool of particles.
+///
+/// Live particles are kept packed at the front of the pool, so spawning and culling never allocate.
+/// When the pool is full new particles are dropped.
+pub struct ParticlePool {
+    particles: Vec<Particle>,
+    alive: usize,
+}
+
+impl ParticlePool {
+    pub fn new(capacity: usize) -> Self {
+        ParticlePool {
+            particles: vec![Particle::dead(); capacity],
+            alive: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn alive(&self) -> usize {
+        self.alive
+    }
+
+    pub fn spawn(&mut self,
+                 position: Vector2<GLfloat>,
+                 velocity: Vector2<GLfloat>,
+                 color: Vector3<GLfloat>,
+                 size: GLfloat,
+                 life: GLfloat) {
+        if self.alive >= self.particles.len() || life <= 0.0 {
+            return;
+        }
+
+        let particle = &mut self.particles[self.alive];
+        particle.position = position;
+        particle.velocity = velocity;
+        particle.color = Vector4::new(color.x, color.y, color.z, 1.0);
+        particle.size = size;
+        particle.life = life;
+        particle.max_life = life;
+        self.alive += 1;
+    }
+
+    /// Ages every particle, fading it out over its lifetime and recycling it once dead.
+    pub fn update(&mut self, dt: f32) {
+        let mut i = 0;
+        while i < self.alive {
+            let dead = {
+                let particle = &mut self.particles[i];
+                particle.life -= dt;
+                particle.position += particle.velocity * dt;
+                particle.color.w = (particle.life / particle.max_life).max(0.0);
+                particle.life <= 0.0
+            };
+
+            if dead {
+                // move the last live particle into the gap
+                self.alive -= 1;
+                self.particles.swap(i, self.alive);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.alive = 0;
+    }
+
+    pub fn iter(&self) -> ::std::slice::Iter<Particle> {
+        self.particles[..self.alive].iter()
+    }
+}
+
+/// Continuous source of particles that follows a `GameObject`.
+#[derive(Debug, Clone)]
+pub struct Emitter {
+    offset: Vector2<GLfloat>,      // spawn point relative to the object's position
+    rate: GLfloat,                 // particles per second
+    life: GLfloat,
+    size: GLfloat,
+    spread: GLfloat,               // random jitter applied to spawn positions
+    inherit_velocity: GLfloat,     // fraction of the object's velocity given to new particles
+    color: Vector3<GLfloat>,
+    pending: GLfloat,              // fractional particles carried over between frames
+}
+
+impl Emitter {
+    pub fn new(rate: GLfloat, life: GLfloat) -> Self {
+        Emitter {
+            offset: Vector2::new(0.0, 0.0),
+            rate: rate,
+            life: life,
+            size: 10.0,
+            spread: 0.0,
+            inherit_velocity: 0.0,
+            color: Vector3::new(1.0, 1.0, 1.0),
+            pending: 0.0,
+        }
+    }
+
+    pub fn with_offset(&mut self, offset: Vector2<GLfloat>) -> &mut Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_size(&mut self, size: GLfloat) -> &mut Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_spread(&mut self, spread: GLfloat) -> &mut Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn with_inherit_velocity(&mut self, inherit_velocity: GLfloat) -> &mut Self {
+        self.inherit_velocity = inherit_velocity;
+        self
+    }
+
+    pub fn with_color(&mut self, color: Vector3<GLfloat>) -> &mut Self {
+        self.color = color;
+        self
+    }
+}
+
+pub struct ParticleManager {
+    pool: ParticlePool,
+    random: Random,
+    shader: Rc<RefCell<Shader>>,
+    texture: Rc<RefCell<Texture>>,
+    instance_data: Vec<GLfloat>,
+    vao: GLuint,
+    instance_vbo: GLuint,
+}
+
+impl ParticleManager {
+    pub fn new(shader: &Rc<RefCell<Shader>>, texture: &Rc<RefCell<Texture>>, capacity: usize) -> Self {
+        let quad: Vec<GLfloat> = vec![
+                0.0, 1.0, 0.0, 1.0,
+                0.0, 0.0, 0.0, 0.0,
+                1.0, 0.0, 1.0, 0.0,
+                1.0, 0.0, 1.0, 0.0,
+                1.0, 1.0, 1.0, 1.0,
+                0.0, 1.0, 0.0, 1.0,
+            ];
+
+        let mut vao: GLuint = 0;
+        let mut quad_vbo: GLuint = 0;
+        let mut instance_vbo: GLuint = 0;
+
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut quad_vbo);
+            gl::GenBuffers(1, &mut instance_vbo);
+
+            buffer_data(quad_vbo, &quad);
+
+            // reserve space for every particle up front, it is refilled each frame
+            gl::BindBuffer(gl::ARRAY_BUFFER, instance_vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (capacity * INSTANCE_FLOATS * size_of::<GLfloat>()) as GLsizeiptr,
+                null(),
+                gl::STREAM_DRAW,
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+            gl::BindVertexArray(vao);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, quad_vbo);
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 4, gl::FLOAT, gl::FALSE, (4 * size_of::<GLfloat>()) as GLint, null());
+
+            let stride = (INSTANCE_FLOATS * size_of::<GLfloat>()) as GLint;
+            gl::BindBuffer(gl::ARRAY_BUFFER, instance_vbo);
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 4, gl::FLOAT, gl::FALSE, stride, null());
+            gl::VertexAttribDivisor(1, 1);
+            gl::EnableVertexAttribArray(2);
+            gl::VertexAttribPointer(2, 4, gl::FLOAT, gl::FALSE, stride,
+                                    (4 * size_of::<GLfloat>()) as *const c_void);
+            gl::VertexAttribDivisor(2, 1);
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+
+        ParticleManager {
+            pool: ParticlePool::new(capacity),
+            random: Random::new(0x5EED),
+            shader: shader.clone(),
+            texture: texture.clone(),
+            instance_data: Vec::with_capacity(capacity * INSTANCE_FLOATS),
+            vao: vao,
+            instance_vbo: instance_vbo,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.pool.update(dt);
+    }
+
+    /// Spawns the particles `emitter` has accumulated over `dt`, at `object`'s position.
+    pub fn emit(&mut self, emitter: &mut Emitter, object: &GameObject, dt: f32) {
+        emitter.pending += emitter.rate * dt;
+
+        while emitter.pending >= 1.0 {
+            emitter.pending -= 1.0;
+
+            let jitter = Vector2::new(
+                self.random.range(-emitter.spread, emitter.spread),
+                self.random.range(-emitter.spread, emitter.spread),
+            );
+            let brightness = self.random.range(0.5, 1.0);
+
+            self.pool.spawn(
+                object.position() + emitter.offset + jitter,
+                object.velocity() * emitter.inherit_velocity,
+                emitter.color * brightness,
+                emitter.size,
+                emitter.life,
+            );
+        }
+    }
+
+    /// Scatters `count` particles from random points within `object`, in the object's colour.
+    pub fn burst(&mut self, object: &GameObject, count: usize, speed: GLfloat, life: GLfloat) {
+        let position = object.position().clone();
+        let size = object.size().clone();
+        let center = position + size * 0.5;
+
+        for _ in 0..count {
+            let origin = Vector2::new(
+                position.x + self.random.next_f32() * size.x,
+                position.y + self.random.next_f32() * size.y,
+            );
+            let direction = origin - center;
+            let direction = if direction.norm() > 0.0 { direction.normalize() } else { Vector2::new(0.0, 1.0) };
+            let velocity = direction * self.random.range(0.25, 1.0) * speed;
+
+            self.pool.spawn(
+                origin,
+                velocity,
+                object.color().clone(),
+                self.random.range(4.0, 10.0),
+                life * self.random.range(0.5, 1.0),
+            );
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pool.clear();
+    }
+
+    /// Draws every live particle in a single instanced call with additive blending.
+    pub fn draw(&mut self) {
+        if self.pool.alive() == 0 {
+            return;
+        }
+
+        self.instance_data.clear();
+        for particle in self.pool.iter() {
+            self.instance_data.extend_from_slice(&[
+                particle.position.x,
+                particle.position.y,
+                particle.size,
+                0.0,
+                particle.color.x,
+                particle.color.y,
+                particle.color.z,
+                particle.color.w,
+            ]);
+        }
+
+        unsafe {
+            self.shader.borrow().enable();
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.instance_vbo);
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                0,
+                (self.instance_data.len() * size_of::<GLfloat>()) as GLsizeiptr,
+                self.instance_data.as_ptr() as *const c_void,
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE);
+
+            gl::ActiveTexture(gl::TEXTURE0);
+            self.texture.borrow().bind();
+            gl::BindVertexArray(self.vao);
+            gl::DrawArraysInstanced(gl::TRIANGLES, 0, 6, self.pool.alive() as GLsizei);
+            gl::BindVertexArray(0);
+
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_particles_once_full() {
+        let mut pool = ParticlePool::new(2);
+        for _ in 0..3 {
+            pool.spawn(Vector2::new(0.0, 0.0), Vector2::new(0.0, 0.0), Vector3::new(1.0, 1.0, 1.0), 1.0, 1.0);
+        }
+        assert_eq!(pool.alive(), 2);
+        assert_eq!(pool.capacity(), 2);
+    }
+
+    #[test]
+    fn recycles_dead_particles() {
+        let mut pool = ParticlePool::new(4);
+        pool.spawn(Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0), Vector3::new(1.0, 1.0, 1.0), 1.0, 0.5);
+        pool.spawn(Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0), Vector3::new(1.0, 1.0, 1.0), 1.0, 2.0);
+
+        pool.update(1.0);
+        assert_eq!(pool.alive(), 1);
+
+        let survivor = pool.iter().next().expect("Should have a survivor");
+        assert_eq!(survivor.position.x, 1.0);
+        assert_eq!(survivor.color.w, 0.5);
+    }
+}
@@ -13,20 +13,40 @@ use std::slice::from_raw_parts;
 
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
 
 use std::os::raw::{c_char, c_int, c_uchar, c_void};
 use std::ptr::null_mut;
 
+use systems::asset_locator::AssetLocator;
 use utilities::shader::{Shader};
 use utilities::texture::Texture;
+use utilities::text_renderer::Font;
+use utilities::font_serialization::read_font_from_file;
+use utilities::manifest_serialization::Manifest;
+use utilities::sound::{Sound, read_sound_from_file};
+
+use std::path::{Path, PathBuf};
 
 #[allow(non_camel_case_types)]
 #[allow(dead_code)]
 include!(concat!(env!("OUT_DIR"), "/SOIL_bindings.rs"));
 
+/// Where loaded textures are kept.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureStorage {
+    GPU,            // an OpenGL texture only
+    GPU_AND_CPU,    // an OpenGL texture, plus a copy for the software renderer
+    CPU,            // only a copy for the software renderer, so no graphics context is needed
+}
+
 pub struct ResourceManager {
     shaders: HashMap<String, Rc<RefCell<Shader>>>,
     textures: HashMap<String, Rc<RefCell<Texture>>>,
+    fonts: HashMap<String, Rc<RefCell<Font>>>,
+    sounds: HashMap<String, Arc<Sound>>,
+    locator: AssetLocator,      // finds the files named when loading
+    texture_storage: TextureStorage,
 }
 
 impl ResourceManager {
@@ -34,16 +54,45 @@ impl ResourceManager {
         ResourceManager {
             shaders: HashMap::new(),
             textures: HashMap::new(),
+            fonts: HashMap::new(),
+            sounds: HashMap::new(),
+            locator: AssetLocator::from_environment(),
+            texture_storage: TextureStorage::GPU,
         }
     }
 
+    /// Chooses where textures loaded from now on are kept.
+    pub fn with_texture_storage(&mut self, texture_storage: TextureStorage) -> &mut Self {
+        self.texture_storage = texture_storage;
+        self
+    }
+
+    pub fn locator_mut(&mut self) -> &mut AssetLocator {
+        &mut self.locator
+    }
+
+    /// Finds the asset file `name` on the search path, for files loaded outside the resource manager.
+    pub fn locate(&self, name: &str) -> Result<PathBuf, String> {
+        self.locator.locate(name)
+    }
+
+    /// Like `locate`, but as a string for the loaders that take one.
+    fn locate_str(&self, name: &str) -> Result<String, String> {
+        let path = self.locate(name)?;
+        path.to_str()
+            .map(|path| path.to_string())
+            .ok_or(format!("Invalid asset path {:?}", path))
+    }
+
     pub fn load_shader(
         &mut self,
         vertex_file: &str,
         fragment_file: &str,
         name: &str,
     ) -> Result<Rc<RefCell<Shader>>,String> {
-        let shader = ResourceManager::load_shader_from_file( vertex_file, fragment_file )?;
+        let vertex_file = self.locate_str(vertex_file)?;
+        let fragment_file = self.locate_str(fragment_file)?;
+        let shader = ResourceManager::load_shader_from_file( &vertex_file, &fragment_file )?;
         self.shaders.insert(
             name.to_string(),
             Rc::new(RefCell::new(shader))
@@ -61,7 +110,8 @@ impl ResourceManager {
         is_alpha: bool,
         name: &str,
     ) -> Result<Rc<RefCell<Texture>>,String> {
-        let texture = ResourceManager::load_texture_from_file( vertex_file, is_alpha)?;
+        let vertex_file = self.locate_str(vertex_file)?;
+        let texture = self.load_texture_from_file(&vertex_file, is_alpha, None, None)?;
         self.textures.insert(
             name.to_string(),
             Rc::new(RefCell::new(texture)),
@@ -73,14 +123,119 @@ impl ResourceManager {
         self.textures.get(name).map(|shdr| shdr.clone())
     }
 
+    /// Loads a BMFont text (.fnt) file along with its atlas pages.
+    ///
+    /// Page files are resolved relative to the directory containing `font_file`.
+    pub fn load_font(
+        &mut self,
+        font_file: &str,
+        name: &str,
+    ) -> Result<Rc<RefCell<Font>>,String> {
+        let font_file = self.locate_str(font_file)?;
+        let font = self.load_font_from_file(&font_file)?;
+        self.fonts.insert(
+            name.to_string(),
+            Rc::new(RefCell::new(font)),
+        );
+        Ok(self.fonts[name].clone())
+    }
+
+    pub fn get_font(&self, name: &str) -> Option<Rc<RefCell<Font>>> {
+        self.fonts.get(name).map(|font| font.clone())
+    }
+
+    /// Loads and fully decodes a WAV or OGG file.
+    ///
+    /// Sounds are shared with the audio thread, so they are reference counted with `Arc`.
+    pub fn load_sound(
+        &mut self,
+        sound_file: &str,
+        name: &str,
+    ) -> Result<Arc<Sound>,String> {
+        let sound_file = self.locate(sound_file)?;
+        let sound = read_sound_from_file(&sound_file)?;
+        self.sounds.insert(
+            name.to_string(),
+            Arc::new(sound),
+        );
+        Ok(self.sounds[name].clone())
+    }
+
+    pub fn get_sound(&self, name: &str) -> Option<Arc<Sound>> {
+        self.sounds.get(name).map(|sound| sound.clone())
+    }
+
+    /// Loads every shader, texture, sound and font listed in `manifest`.
+    ///
+    /// Loading carries on past assets that fail, so that every failure is reported together in
+    /// the error, one per line.
+    pub fn load_manifest(&mut self, manifest: &Manifest) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        for shader in manifest.shaders.iter() {
+            if let Err(e) = self.load_shader(&shader.vertex_file, &shader.fragment_file, &shader.name) {
+                errors.push(format!("shader {} - {}", shader.name, e));
+            }
+        }
+
+        for texture in manifest.textures.iter() {
+            let loaded = self.locate_str(&texture.file).and_then(|file| {
+                self.load_texture_from_file(&file, texture.is_alpha, texture.filter, texture.wrap)
+            });
+            match loaded {
+                Ok(loaded) => {
+                    self.textures.insert(texture.name.clone(), Rc::new(RefCell::new(loaded)));
+                }
+                Err(e) => errors.push(format!("texture {} - {}", texture.name, e)),
+            }
+        }
+
+        for sound in manifest.sounds.iter() {
+            if let Err(e) = self.load_sound(&sound.file, &sound.name) {
+                errors.push(format!("sound {} - {}", sound.name, e));
+            }
+        }
+
+        for font in manifest.fonts.iter() {
+            if let Err(e) = self.load_font(&font.file, &font.name) {
+                errors.push(format!("font {} - {}", font.name, e));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
     pub fn clear(&mut self) {
         {
             let mut new_shaders = HashMap::new();
             let mut new_textures = HashMap::new();
+            let mut new_fonts = HashMap::new();
+            let mut new_sounds = HashMap::new();
 
             swap(&mut new_shaders, &mut self.shaders);
             swap(&mut new_textures, &mut self.textures);
+            swap(&mut new_fonts, &mut self.fonts);
+            swap(&mut new_sounds, &mut self.sounds);
+        }
+    }
+
+    fn load_font_from_file(&self, font_file: &str) -> Result<Font, String> {
+        let metrics = read_font_from_file(&font_file)?;
+        let directory = Path::new(font_file).parent().unwrap_or(Path::new(""));
+
+        let mut pages = Vec::new();
+        for page in metrics.pages.iter() {
+            let path = directory.join(page);
+            let path = path.to_str().ok_or(format!("Invalid font page path {:?}", path))?;
+            let texture = self.load_texture_from_file(path, true, None, None)?;
+            pages.push(Rc::new(RefCell::new(texture)));
         }
+
+        Ok(Font::new(metrics, pages))
     }
 
     fn load_shader_from_file(vertex_file: &str, fragment_file: &str) -> Result<Shader, String> {
@@ -106,14 +261,16 @@ impl ResourceManager {
         Shader::new(&vertex_source, &fragment_source)
     }
 
-    fn load_texture_from_file(path: &str, alpha: bool) -> Result<Texture,String> {
+    /// `filter` and `wrap` apply to both directions, and are left to the texture's defaults when `None`.
+    fn load_texture_from_file(&self, path: &str, alpha: bool, filter: Option<GLuint>, wrap: Option<GLuint>) -> Result<Texture,String> {
 
         let mut file = CString::new(path)
             .expect("| ERROR::RESOURCE_MANAGER: Invalid file string for texture.");
         let mut file = file.as_ptr();
         let mut width: c_int = 0;
         let mut height: c_int = 0;
-        let mut force_channels = SOIL_LOAD_AUTO;
+        // always four channels, whatever the file holds, so the data matches the format it is described with
+        let mut force_channels = SOIL_LOAD_RGBA;
 
 
         unsafe {
@@ -129,10 +286,22 @@ impl ResourceManager {
                 return Err( format!( "Unable to load image at location {}", path));
             }
 
+            // the data has four channels either way, but only textures loaded with `alpha` keep the fourth
             let mut texture = Texture::new(width as GLuint, height as GLuint, image as *const c_void);
-            texture.with_alpha(channels == 4);
+            texture.with_alpha(alpha)
+                .with_image_format(gl::RGBA)
+                .with_cpu_copy(self.texture_storage == TextureStorage::GPU_AND_CPU);
+            if let Some(filter) = filter {
+                texture.with_filter_min(filter).with_filter_max(filter);
+            }
+            if let Some(wrap) = wrap {
+                texture.with_wrap_S(wrap).with_wrap_T(wrap);
+            }
 
-            let mut texture = texture.build();
+            let mut texture = match self.texture_storage {
+                TextureStorage::CPU => texture.build_on_cpu(),
+                _ => texture.build(),
+            };
 
             SOIL_free_image_data(image);
 
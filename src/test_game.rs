@@ -1,7 +1,7 @@
 extern crate gl;
 
-use super::resource_manager::ResourceManager;
-use super::shader::Shader;
+use systems::resource_manager::ResourceManager;
+use utilities::shader::Shader;
 use gl::types::{GLfloat, GLint, GLuint};
 use std::cell::RefCell;
 use std::mem::size_of;
@@ -26,8 +26,8 @@ impl TestGame {
             .resource_manager
             .borrow_mut()
             .load_shader(
-                "/home/gopiandcode/Documents/Rust/gui-base/shaders/triangle.vs",
-                "/home/gopiandcode/Documents/Rust/gui-base/shaders/triangle.frag",
+                "shaders/triangle.vs",
+                "shaders/triangle.frag",
                 "triangle",
             )
             .expect("Could not load triangle shader");
@@ -0,0 +1,123 @@
+extern crate sdl2;
+
+use systems::input_manager::{ControlScheme, Input, KeyBindings, INPUTS};
+
+use std::fs::File;
+use std::path::Path;
+use std::io::{BufRead, BufReader, Write};
+
+use sdl2::keyboard::Keycode;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_correct_format() {
+        let bindings = read_bindings_from_string("# comment\n\nleft = Q, Left\nlaunch = space\nscheme = touch\n").expect("Should work");
+        assert_eq!(bindings.keys(Input::LEFT), &[Keycode::Q, Keycode::Left]);
+        assert_eq!(bindings.keys(Input::LAUNCH), &[Keycode::Space]);
+        assert_eq!(bindings.scheme(), ControlScheme::TOUCH);
+        // unlisted actions keep their defaults
+        assert_eq!(bindings.keys(Input::PAUSE), KeyBindings::new().keys(Input::PAUSE));
+    }
+
+    #[test]
+    fn reads_back_written_bindings() {
+        let mut bindings = KeyBindings::new();
+        bindings.set(Input::BACK, vec![Keycode::Backspace, Keycode::Tab]);
+        bindings.set_scheme(ControlScheme::MOUSE);
+
+        let mut bytes = Vec::new();
+        write_bindings_to_writer(&mut bytes, &bindings).expect("Should work");
+        let string = String::from_utf8(bytes).expect("Should work");
+        assert_eq!(read_bindings_from_string(&string).expect("Should work"), bindings);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_unknown_keys() {
+        read_bindings_from_string("left = NotAKey\n").expect("Should work");
+    }
+
+    #[test]
+    fn counts_lines_from_one() {
+        let e = read_bindings_from_string("left = NotAKey\n").err().expect("Should work");
+        assert!(e.ends_with("at line 1"), "{}", e);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_unknown_actions() {
+        read_bindings_from_string("jump = Space\n").expect("Should work");
+    }
+}
+
+pub fn read_bindings_from_file<T : AsRef<Path>>(filename: &T) -> Result<KeyBindings, String> {
+    let file = File::open(filename).map_err(|e| e.to_string())?;
+    read_bindings_from_reader(&mut BufReader::new(file))
+}
+
+pub fn read_bindings_from_string(string : &str) -> Result<KeyBindings, String> {
+    read_bindings_from_reader(&mut BufReader::new(string.as_bytes()))
+}
+
+/// Reads lines of the form `action = key, key, ...`, using SDL's key names, and an optional
+/// `scheme = keyboard|mouse|touch` line choosing how the paddle is steered.
+///
+/// Actions that are not listed keep their default bindings. Blank lines and lines starting
+/// with `#` are ignored.
+pub fn read_bindings_from_reader<R : BufRead>(reader : &mut R) -> Result<KeyBindings, String> {
+    let mut bindings = KeyBindings::new();
+
+    for (number, line) in (1..).zip(reader.lines()) {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("#") {
+            continue;
+        }
+
+        let mut parts = line.splitn(2, "=");
+        let name = parts.next().unwrap_or("").trim();
+        let keys = parts.next()
+            .ok_or(format!("Invalid bindings format - expected action = keys at line {}", number))?;
+
+        if name == "scheme" {
+            let scheme = ControlScheme::from_name(keys.trim())
+                .ok_or(format!("Invalid bindings format - unknown control scheme {} at line {}", keys.trim(), number))?;
+            bindings.set_scheme(scheme);
+            continue;
+        }
+
+        let input = Input::from_name(name)
+            .ok_or(format!("Invalid bindings format - unknown action {} at line {}", name, number))?;
+
+        let mut keycodes = Vec::new();
+        // key names can contain spaces, such as "Left Shift", so they are comma separated
+        for key in keys.split(",").map(|key| key.trim()) {
+            let keycode = Keycode::from_name(key)
+                .ok_or(format!("Invalid bindings format - unknown key {} at line {}", key, number))?;
+            keycodes.push(keycode);
+        }
+        bindings.set(input, keycodes);
+    }
+
+    Ok(bindings)
+}
+
+pub fn write_bindings_to_file<T : AsRef<Path>>(filename: &T, bindings: &KeyBindings) -> Result<(), String> {
+    let mut file = File::create(filename).map_err(|e| e.to_string())?;
+    write_bindings_to_writer(&mut file, bindings)
+}
+
+pub fn write_bindings_to_writer<W : Write>(writer: &mut W, bindings: &KeyBindings) -> Result<(), String> {
+    writeln!(writer, "# action = key, key, ...").map_err(|e| e.to_string())?;
+    writeln!(writer, "scheme = {}", bindings.scheme().name()).map_err(|e| e.to_string())?;
+    for input in INPUTS.iter() {
+        let keys = bindings.keys(*input).iter()
+            .map(|keycode| keycode.name())
+            .collect::<Vec<String>>();
+        writeln!(writer, "{} = {}", input.name(), keys.join(", ")).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,152 @@
+extern crate nalgebra;
+
+use utilities::viewport::Viewport;
+
+use gl::types::GLfloat;
+use nalgebra::base::{Matrix4, Vector2};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fractional_scaling_fills_the_window() {
+        let mut canvas = Canvas::new();
+        canvas.fit(2000, 1200);
+
+        assert!((canvas.scale() - 2.0).abs() < 1e-6);
+        assert_eq!(*canvas.viewport(), Viewport::centered(2000, 1200, 1600, 1200));
+    }
+
+    #[test]
+    fn integer_scaling_rounds_down_to_whole_pixels() {
+        let mut canvas = Canvas::new();
+        canvas.with_scale_mode(ScaleMode::INTEGER).fit(2000, 1000);
+
+        assert!((canvas.scale() - 1.0).abs() < 1e-6);
+        assert_eq!(*canvas.viewport(), Viewport::centered(2000, 1000, 800, 600));
+    }
+
+    #[test]
+    fn integer_scaling_shrinks_to_fit_small_windows() {
+        let mut canvas = Canvas::new();
+        canvas.with_scale_mode(ScaleMode::INTEGER).fit(400, 400);
+
+        assert!((canvas.scale() - 0.5).abs() < 1e-6);
+        assert_eq!(*canvas.viewport(), Viewport::centered(400, 400, 400, 300));
+    }
+}
+
+/// Width of the game, in the logical units all game code works in.
+pub const VIRTUAL_WIDTH: GLfloat = 800.0;
+/// Height of the game, in the logical units all game code works in.
+pub const VIRTUAL_HEIGHT: GLfloat = 600.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScaleMode {
+    FRACTIONAL,     // fill as much of the window as the aspect ratio allows
+    INTEGER,        // only whole multiples of the canvas size, so pixels stay sharp
+}
+
+impl ScaleMode {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ScaleMode::FRACTIONAL => "fractional",
+            ScaleMode::INTEGER => "integer",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fractional" => Some(ScaleMode::FRACTIONAL),
+            "integer" => Some(ScaleMode::INTEGER),
+            _ => None,
+        }
+    }
+}
+
+/// The `VIRTUAL_WIDTH` by `VIRTUAL_HEIGHT` area the game is drawn in, scaled to fit whatever
+/// window it is shown in.
+///
+/// Everything in the game is positioned in the canvas's logical units, and only the viewport
+/// changes with the size of the window.
+pub struct Canvas {
+    scale_mode: ScaleMode,
+    scale: GLfloat,          // drawable pixels per logical unit
+    viewport: Viewport,
+}
+
+impl Canvas {
+    pub fn new() -> Self {
+        Canvas {
+            scale_mode: ScaleMode::FRACTIONAL,
+            scale: 1.0,
+            viewport: Viewport::full(VIRTUAL_WIDTH as u32, VIRTUAL_HEIGHT as u32),
+        }
+    }
+
+    pub fn with_scale_mode(&mut self, scale_mode: ScaleMode) -> &mut Self {
+        self.scale_mode = scale_mode;
+        self
+    }
+
+    /// Places the canvas in the middle of a drawable area of the given size in pixels, as large
+    /// as the scale mode allows.
+    pub fn fit(&mut self, drawable_width: u32, drawable_height: u32) {
+        let drawable_width = drawable_width.max(1);
+        let drawable_height = drawable_height.max(1);
+
+        let fitted = (drawable_width as GLfloat / VIRTUAL_WIDTH).min(drawable_height as GLfloat / VIRTUAL_HEIGHT);
+        // windows smaller than the canvas still get the whole game, just not crisply
+        self.scale = match self.scale_mode {
+            ScaleMode::INTEGER if fitted >= 1.0 => fitted.floor(),
+            _ => fitted,
+        };
+
+        self.viewport = match self.scale_mode {
+            ScaleMode::FRACTIONAL => Viewport::letterbox(drawable_width, drawable_height, VIRTUAL_WIDTH / VIRTUAL_HEIGHT),
+            ScaleMode::INTEGER => Viewport::centered(
+                drawable_width,
+                drawable_height,
+                (VIRTUAL_WIDTH * self.scale).round() as u32,
+                (VIRTUAL_HEIGHT * self.scale).round() as u32,
+            ),
+        };
+    }
+
+    /// Orthographic projection covering the canvas, with y pointing up.
+    pub fn projection(&self) -> Matrix4<GLfloat> {
+        Matrix4::new_orthographic(
+            0.0,                // left
+            VIRTUAL_WIDTH,      // right
+            0.0,                // bottom
+            VIRTUAL_HEIGHT,     // top
+            -1.0,               // znear
+            1.0,                // zfar
+        )
+    }
+
+    pub fn width(&self) -> GLfloat {
+        VIRTUAL_WIDTH
+    }
+
+    pub fn height(&self) -> GLfloat {
+        VIRTUAL_HEIGHT
+    }
+
+    pub fn size(&self) -> Vector2<GLfloat> {
+        Vector2::new(VIRTUAL_WIDTH, VIRTUAL_HEIGHT)
+    }
+
+    pub fn scale_mode(&self) -> ScaleMode {
+        self.scale_mode
+    }
+
+    pub fn scale(&self) -> GLfloat {
+        self.scale
+    }
+
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+}
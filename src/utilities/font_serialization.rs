@@ -0,0 +1,198 @@
+extern crate gl;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::io::{BufRead, BufReader};
+
+use gl::types::GLfloat;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static FONT: &'static str = "info face=\"Test\" size=32 bold=0 italic=0\ncommon lineHeight=38 base=30 scaleW=256 scaleH=128 pages=1 packed=0\npage id=0 file=\"test.png\"\nchars count=2\nchar id=65   x=2     y=4     width=20    height=24    xoffset=1     yoffset=6     xadvance=22    page=0  chnl=15\nchar id=86   x=24    y=4     width=21    height=24    xoffset=0     yoffset=6     xadvance=21    page=0  chnl=15\nkernings count=1\nkerning first=65  second=86  amount=-2\n";
+
+    #[test]
+    fn reads_common_block_and_pages() {
+        let metrics = read_font_from_string(FONT).expect("Should work");
+        assert_eq!(metrics.line_height, 38.0);
+        assert_eq!(metrics.base, 30.0);
+        assert_eq!(metrics.scale_width, 256.0);
+        assert_eq!(metrics.pages, vec!["test.png".to_string()]);
+    }
+
+    #[test]
+    fn reads_glyphs_and_kerning() {
+        let metrics = read_font_from_string(FONT).expect("Should work");
+        let glyph = metrics.glyphs.get(&'A').expect("Should contain A");
+        assert_eq!(glyph.x, 2.0);
+        assert_eq!(glyph.x_advance, 22.0);
+        assert_eq!(metrics.kerning('A', 'V'), -2.0);
+        assert_eq!(metrics.kerning('V', 'A'), 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_fonts_without_common_block() {
+        read_font_from_string("page id=0 file=\"test.png\"\n").expect("Should work");
+    }
+}
+
+/// Placement of a single character within a font atlas, in atlas pixels.
+#[derive(Debug, Clone)]
+pub struct Glyph {
+    pub x: GLfloat,
+    pub y: GLfloat,
+    pub width: GLfloat,
+    pub height: GLfloat,
+    pub x_offset: GLfloat,   // offset from the pen position to the left of the glyph
+    pub y_offset: GLfloat,   // offset from the top of the line to the top of the glyph
+    pub x_advance: GLfloat,  // distance to move the pen after drawing the glyph
+    pub page: usize,
+}
+
+/// Metrics read from an AngelCode BMFont text (.fnt) file.
+#[derive(Debug, Clone)]
+pub struct FontMetrics {
+    pub line_height: GLfloat,
+    pub base: GLfloat,
+    pub scale_width: GLfloat,
+    pub scale_height: GLfloat,
+    pub pages: Vec<String>,
+    pub glyphs: HashMap<char, Glyph>,
+    pub kernings: HashMap<(char, char), GLfloat>,
+}
+
+impl FontMetrics {
+    pub fn kerning(&self, first: char, second: char) -> GLfloat {
+        self.kernings.get(&(first, second)).cloned().unwrap_or(0.0)
+    }
+}
+
+pub fn read_font_from_file<T : AsRef<Path>>(filename: &T) -> Result<FontMetrics, String> {
+    let file = File::open(filename).map_err(|e| e.to_string())?;
+    read_font_from_reader(&mut BufReader::new(file))
+}
+
+pub fn read_font_from_string(string : &str) -> Result<FontMetrics, String> {
+    read_font_from_reader(&mut BufReader::new(string.as_bytes()))
+}
+
+/// Splits a BMFont line into its tag and `key=value` pairs, honouring quoted values.
+fn parse_line(line: &str) -> (String, HashMap<String, String>) {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in line.trim().chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ' ' | '\t' if !quoted => {
+                if !current.is_empty() {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    let mut tokens = tokens.into_iter();
+    let tag = tokens.next().unwrap_or(String::new());
+    let values = tokens
+        .filter_map(|token| {
+            let mut parts = token.splitn(2, "=");
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => Some((key.to_string(), value.to_string())),
+                _ => None,
+            }
+        })
+        .collect();
+
+    (tag, values)
+}
+
+fn get_number(values: &HashMap<String, String>, key: &str, line: usize) -> Result<GLfloat, String> {
+    values.get(key)
+        .ok_or(format!("Invalid font format - missing {} at line {}", key, line))?
+        .parse::<GLfloat>()
+        .map_err(|_| format!("Invalid font format - {} is not a number at line {}", key, line))
+}
+
+fn get_char(values: &HashMap<String, String>, key: &str, line: usize) -> Result<char, String> {
+    let id = get_number(values, key, line)? as u32;
+    ::std::char::from_u32(id).ok_or(format!("Invalid font format - {} is not a character at line {}", key, line))
+}
+
+pub fn read_font_from_reader<R : BufRead>(reader : &mut R) -> Result<FontMetrics, String> {
+    let mut common = None;
+    let mut pages = Vec::new();
+    let mut glyphs = HashMap::new();
+    let mut kernings = HashMap::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        let (tag, values) = parse_line(&line);
+
+        match tag.as_str() {
+            "common" => {
+                common = Some((
+                    get_number(&values, "lineHeight", i)?,
+                    get_number(&values, "base", i)?,
+                    get_number(&values, "scaleW", i)?,
+                    get_number(&values, "scaleH", i)?,
+                ));
+            }
+            "page" => {
+                let id = get_number(&values, "id", i)? as usize;
+                let file = values.get("file")
+                    .ok_or(format!("Invalid font format - missing file at line {}", i))?;
+                if pages.len() <= id {
+                    pages.resize(id + 1, String::new());
+                }
+                pages[id] = file.clone();
+            }
+            "char" => {
+                let glyph = Glyph {
+                    x: get_number(&values, "x", i)?,
+                    y: get_number(&values, "y", i)?,
+                    width: get_number(&values, "width", i)?,
+                    height: get_number(&values, "height", i)?,
+                    x_offset: get_number(&values, "xoffset", i)?,
+                    y_offset: get_number(&values, "yoffset", i)?,
+                    x_advance: get_number(&values, "xadvance", i)?,
+                    page: get_number(&values, "page", i)? as usize,
+                };
+                glyphs.insert(get_char(&values, "id", i)?, glyph);
+            }
+            "kerning" => {
+                let first = get_char(&values, "first", i)?;
+                let second = get_char(&values, "second", i)?;
+                kernings.insert((first, second), get_number(&values, "amount", i)?);
+            }
+            // info, chars and kernings only carry information we do not need
+            _ => (),
+        }
+    }
+
+    let (line_height, base, scale_width, scale_height) =
+        common.ok_or("Invalid font format - missing common block")?;
+
+    if pages.is_empty() {
+        return Err(format!("Invalid font format - no pages"));
+    }
+
+    Ok(FontMetrics {
+        line_height: line_height,
+        base: base,
+        scale_width: scale_width,
+        scale_height: scale_height,
+        pages: pages,
+        glyphs: glyphs,
+        kernings: kernings,
+    })
+}
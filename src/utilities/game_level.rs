@@ -1,10 +1,11 @@
 extern crate gl;
 extern crate nalgebra;
 
-use utilities::sprite_renderer::SpriteRenderer;
+use utilities::renderer::Renderer;
 use objects::game_object::{GameObject, GameObjectBuilder};
 use systems::resource_manager::ResourceManager;
 use utilities::game_serialization::{read_from_file, read_from_string};
+use utilities::canvas::{VIRTUAL_WIDTH, VIRTUAL_HEIGHT};
 
 use std::cmp;
 use std::cell::RefCell;
@@ -18,40 +19,41 @@ use gl::types::{GLuint, GLfloat};
 use nalgebra::base::{Matrix4, Vector2, Vector3};
 
 
+/// The bricks of a level, filling the whole canvas.
+///
+/// Bricks only name their textures, so levels can be built and played without a graphics context.
 pub struct GameLevel {
     bricks: Vec<GameObject>,
-    resource_manager: Rc<RefCell<ResourceManager>>,
+    layout: Vec<u32>,           // layout the level was originally loaded with
     level_height: GLuint,
     level_width: GLuint,
 }
 
 
-fn create_objects(resource_manager : &ResourceManager,
-                  level: &Vec<u32>,
-                  level_width: GLuint, level_height: GLuint,
-                  screen_width: GLuint, screen_height: GLuint) -> Result<Vec<GameObject>, String> {
+/// Position and size of the brick in column `i` and row `j`, with the first row at the top of the canvas.
+fn brick_transform(i: GLuint, j: GLuint, level_width: GLuint, level_height: GLuint) -> (Vector2<GLfloat>, Vector2<GLfloat>) {
+    let unit_width = VIRTUAL_WIDTH / (level_width as f32);
+    let unit_height = VIRTUAL_HEIGHT / (level_height as f32);
 
-    let unit_width = (screen_width as f32) / (level_width as f32);
-    let unit_height = (screen_height as f32) / (level_height as f32);
+    // positions are the bottom left corner, and y points up
+    let position = Vector2::new(
+        unit_width * i as GLfloat,
+        VIRTUAL_HEIGHT - unit_height * (j + 1) as GLfloat);
+    (position, Vector2::new(unit_width, unit_height))
+}
+
+fn create_objects(level: &Vec<u32>, level_width: GLuint, level_height: GLuint) -> Result<Vec<GameObject>, String> {
 
     let base_color = Vector3::new(0.8, 0.8, 0.7);
-    let size_vector = Vector2::new(unit_width, unit_height);
     let mut bricks = Vec::new();
-    let mut base_pos = Vector2::new(0.0, 0.0);
 
     for j in 0..level_height {
         for i in 0..level_width {
             let index = j * level_width + i;
             let value = *level.get(index as usize).ok_or(format!("invalid Game level input array"))?;
-                 {
-                    base_pos.x = unit_width * i as GLfloat;
-                    base_pos.y = (screen_height as f32) - unit_height * j as GLfloat
-                }
-                let pos = base_pos.clone();
-                let size = size_vector.clone();
+            let (pos, size) = brick_transform(i, j, level_width, level_height);
             if value == 1 {
-                let texture = resource_manager.get_texture("block_solid").ok_or("Could not load block_solid texture.")?;
-                let mut obj = GameObject::new(&texture);
+                let mut obj = GameObject::new("block_solid");
                 obj.with_position(pos)
                     .with_size(size)
                     .with_color(base_color.clone())
@@ -60,7 +62,6 @@ fn create_objects(resource_manager : &ResourceManager,
                 bricks.push(obj);
             } else if value > 1 {
 
-                let texture = resource_manager.get_texture("block").ok_or("Could not load block texture")?;
                 let mut color = Vector3::new(1.0, 1.0, 1.0);
 
                 if value == 2 {
@@ -109,7 +110,7 @@ fn create_objects(resource_manager : &ResourceManager,
                     };
                 }
 
-                let mut obj = GameObject::new(&texture);
+                let mut obj = GameObject::new("block");
                 obj.with_position(pos)
                     .with_size(size)
                     .with_color(color);
@@ -122,104 +123,59 @@ fn create_objects(resource_manager : &ResourceManager,
     Ok(bricks)
 }
 
-fn update_scaling(objects: &mut Vec<GameObject>,
-                  level_width: GLuint, level_height: GLuint,
-                  screen_width: GLuint, screen_height: GLuint) {
-    let unit_width = (screen_width as f32) / (level_width as f32);
-    let unit_height = (screen_height as f32) / (level_height as f32);
-
-    let base_color = Vector3::new(0.8, 0.8, 0.7);
-    let size_vector = Vector2::new(unit_width, unit_height);
-    let mut base_pos = Vector2::new(0.0, 0.0);
-
-    for j in 0..level_height {
-        for i in 0..level_width {
-            let index = j * level_width + i;
-
-            {
-                base_pos.x = (unit_width * i as GLfloat);
-                base_pos.y = (unit_height * j as GLfloat);
-            }
-
-            let mut obj : &mut GameObject = objects.get_mut(index as usize).expect("invalid Game level input array");
-            let pos = &base_pos;
-            let size = &size_vector;
-            let rotation = obj.get_rotation();
-
-            obj.update_transform(pos, size, rotation);
-
-        }
-    }
-}
-
-
 impl GameLevel {
-    pub fn new(resource_manager: &Rc<RefCell<ResourceManager>>,
-               elements: &Vec<u32>,
+    pub fn new(elements: &Vec<u32>,
                level_width: GLuint,
-               level_height: GLuint,
-               screen_width: GLuint,
-               screen_height: GLuint) -> Result<Self, String> {
+               level_height: GLuint) -> Result<Self, String> {
         let objects = create_objects(
-           &resource_manager.borrow(),
             elements,
             level_width,
-            level_height,
-            screen_width,
-            screen_height )?;
+            level_height )?;
         Ok(GameLevel {
             bricks: objects,
-            resource_manager: resource_manager.clone(),
+            layout: elements.clone(),
             level_height: level_height,
             level_width: level_width,
         })
     }
 
-    pub fn from_file<T : AsRef<Path>>(resource_manager: &Rc<RefCell<ResourceManager>>,
-                                      file : &T,
-                                      screen_width: GLuint,
-                                      screen_height: GLuint) -> Result<GameLevel, String> {
+    pub fn from_file<T : AsRef<Path>>(file : &T) -> Result<GameLevel, String> {
         let (level,(level_width, level_height)) = read_from_file(file)?;
         GameLevel::new(
-            resource_manager,
             &level,
             level_width,
-            level_height,
-            screen_width,
-            screen_height
+            level_height
         )
     }
 
-    pub fn from_string(resource_manager: &Rc<RefCell<ResourceManager>>,
-                       level : &str,
-                       screen_width: GLuint,
-                       screen_height: GLuint) -> Result<GameLevel, String> {
+    pub fn from_string(level : &str) -> Result<GameLevel, String> {
         let(level,(level_width, level_height)) = read_from_string(level)?;
          GameLevel::new(
-            resource_manager,
             &level,
             level_width,
-            level_height,
-            screen_width,
-            screen_height
+            level_height
         )
     }
 
-    pub fn update_screen_size(&mut self, screen_width: GLuint, screen_height: GLuint) {
-
-        let level_width = self.level_width;
-        let level_height = self.level_height;
-
-        update_scaling(&mut self.bricks, level_width, level_height, screen_width, screen_height);
+    /// Restores every brick to the layout the level was originally loaded with.
+    pub fn reset(&mut self) -> Result<(), String> {
+        self.bricks = create_objects(
+            &self.layout,
+            self.level_width,
+            self.level_height )?;
+        Ok(())
     }
 
-
-    pub fn draw(&mut self, renderer: &mut SpriteRenderer) {
+    pub fn draw(&mut self, renderer: &mut Renderer, resources: &ResourceManager) {
         for brick in self.bricks.iter_mut() {
-            brick.draw(renderer);
+            brick.draw(renderer, resources);
         }
     }
 
+    pub fn bricks_mut(&mut self) -> &mut Vec<GameObject> {
+        &mut self.bricks
+    }
+
     pub fn is_completed(&self) -> bool {
         for brick in self.bricks.iter() {
             if !brick.is_completed() {
@@ -0,0 +1,408 @@
+extern crate gl;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::io::{BufRead, BufReader};
+
+use gl::types::GLuint;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_correct_format() {
+        let manifest = read_manifest_from_string(
+            "# comment\n\
+             menu_music = \"music/menu.ogg\"\n\
+             \n\
+             [[shader]]\n\
+             name = \"sprite\"\n\
+             vertex = \"shaders/sprite.vs\"\n\
+             fragment = \"shaders/sprite.frag\"\n\
+             \n\
+             [[texture]]\n\
+             name = \"face\"   # trailing comment\n\
+             file = \"textures/face.png\"\n\
+             alpha = true\n\
+             filter = \"nearest\"\n\
+             wrap = \"clamp\"\n\
+             \n\
+             [[texture]]\n\
+             name = \"block\"\n\
+             file = \"textures/block.png\"\n\
+             \n\
+             [[sound]]\n\
+             name = \"brick\"\n\
+             file = \"audio/brick.wav\"\n\
+             \n\
+             [[font]]\n\
+             name = \"default\"\n\
+             file = \"fonts/dejavu.fnt\"\n\
+             \n\
+             [[level]]\n\
+             file = \"levels/one.lvl\"\n\
+             music = \"music/one.wav\"\n\
+             \n\
+             [[level]]\n\
+             file = \"levels/two.lvl\"\n").expect("Should work");
+
+        assert_eq!(manifest.shaders, vec![ShaderEntry {
+            name: "sprite".to_string(),
+            vertex_file: "shaders/sprite.vs".to_string(),
+            fragment_file: "shaders/sprite.frag".to_string(),
+        }]);
+        assert_eq!(manifest.textures[0], TextureEntry {
+            name: "face".to_string(),
+            file: "textures/face.png".to_string(),
+            is_alpha: true,
+            filter: Some(gl::NEAREST),
+            wrap: Some(gl::CLAMP_TO_EDGE),
+        });
+        assert_eq!((manifest.textures[1].is_alpha, manifest.textures[1].filter), (false, None));
+        assert_eq!(manifest.sounds[0].name, "brick");
+        assert_eq!(manifest.fonts[0].file, "fonts/dejavu.fnt");
+        assert_eq!(manifest.levels.menu_music, Some("music/menu.ogg".to_string()));
+        assert_eq!(manifest.levels.levels.len(), 2);
+        assert_eq!(manifest.levels.levels[1].music, None);
+    }
+
+    #[test]
+    fn reads_the_shipped_manifest() {
+        let manifest = read_manifest_from_string(include_str!("../../config/assets.toml")).expect("Should work");
+        assert!(manifest.textures.iter().any(|texture| texture.name == "face" && texture.is_alpha));
+        assert_eq!(manifest.levels.levels.len(), 4);
+    }
+
+    #[test]
+    fn reads_escaped_strings() {
+        let manifest = read_manifest_from_string("[[level]]\nfile = \"levels/\\\"quoted\\\" # not a comment.lvl\"\n").expect("Should work");
+        assert_eq!(manifest.levels.levels[0].file, "levels/\"quoted\" # not a comment.lvl");
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_unknown_texture_options() {
+        read_manifest_from_string("[[texture]]\nname = \"face\"\nfile = \"textures/face.png\"\nfilter = \"cubic\"\n\n[[level]]\nfile = \"levels/one.lvl\"\n").expect("Should work");
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_unknown_keys() {
+        read_manifest_from_string("[[level]]\nfile = \"levels/one.lvl\"\nmusik = \"music/one.wav\"\n").expect("Should work");
+    }
+
+    #[test]
+    fn counts_lines_from_one() {
+        let e = read_manifest_from_string("[level]\nfile = \"levels/one.lvl\"\n").err().expect("Should work");
+        assert!(e.ends_with("at line 1"), "{}", e);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_entries_missing_a_file() {
+        read_manifest_from_string("[[sound]]\nname = \"brick\"\n\n[[level]]\nfile = \"levels/one.lvl\"\n").expect("Should work");
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_manifests_without_levels() {
+        read_manifest_from_string("[[sound]]\nname = \"brick\"\nfile = \"audio/brick.wav\"\n").expect("Should work");
+    }
+}
+
+pub const MANIFEST_FILE: &str = "config/assets.toml";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderEntry {
+    pub name: String,
+    pub vertex_file: String,
+    pub fragment_file: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureEntry {
+    pub name: String,
+    pub file: String,
+    pub is_alpha: bool,
+    pub filter: Option<GLuint>,     // both minifying and magnifying, left to the texture's default if unset
+    pub wrap: Option<GLuint>,       // both axes, left to the texture's default if unset
+}
+
+/// A named file, for assets with no other options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileEntry {
+    pub name: String,
+    pub file: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelEntry {
+    pub file: String,
+    pub music: Option<String>,  // track streamed while the level is being played
+}
+
+/// The levels making up the game, in the order they are played, and the music to go with them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelList {
+    pub menu_music: Option<String>,
+    pub levels: Vec<LevelEntry>,
+}
+
+impl LevelList {
+    pub fn new() -> Self {
+        LevelList {
+            menu_music: None,
+            levels: Vec::new(),
+        }
+    }
+}
+
+/// Every asset the game loads, along with the levels in the order they are played.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub shaders: Vec<ShaderEntry>,
+    pub textures: Vec<TextureEntry>,
+    pub sounds: Vec<FileEntry>,
+    pub fonts: Vec<FileEntry>,
+    pub levels: LevelList,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    STRING(String),
+    BOOLEAN(bool),
+}
+
+/// The keys of one `[[kind]]` table, or of the top of the file when `kind` is empty.
+struct Table {
+    kind: String,
+    line: usize,                                // where the table starts, for errors
+    values: HashMap<String, (Value, usize)>,    // each value with the line it is on
+}
+
+impl Table {
+    fn new(kind: &str, line: usize) -> Self {
+        Table {
+            kind: kind.to_string(),
+            line: line,
+            values: HashMap::new(),
+        }
+    }
+
+    /// Takes the string `key` out of the table, if it is there.
+    fn string(&mut self, key: &str) -> Result<Option<String>, String> {
+        match self.values.remove(key) {
+            Some((Value::STRING(value), _)) => Ok(Some(value)),
+            Some((_, line)) => Err(format!("{} must be a string at line {}", key, line)),
+            None => Ok(None),
+        }
+    }
+
+    fn required_string(&mut self, key: &str) -> Result<String, String> {
+        let line = self.line;
+        let kind = self.kind.clone();
+        self.string(key)?
+            .ok_or(format!("[[{}]] is missing {} at line {}", kind, key, line))
+    }
+
+    fn boolean(&mut self, key: &str) -> Result<Option<bool>, String> {
+        match self.values.remove(key) {
+            Some((Value::BOOLEAN(value), _)) => Ok(Some(value)),
+            Some((_, line)) => Err(format!("{} must be true or false at line {}", key, line)),
+            None => Ok(None),
+        }
+    }
+
+    /// Fails on any key that was not taken out of the table.
+    fn finish(self) -> Result<(), String> {
+        match self.values.iter().min_by_key(|&(_, &(_, line))| line) {
+            Some((key, &(_, line))) => Err(format!("unknown key {} at line {}", key, line)),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn read_manifest_from_file<T : AsRef<Path>>(filename: &T) -> Result<Manifest, String> {
+    let file = File::open(filename).map_err(|e| e.to_string())?;
+    read_manifest_from_reader(&mut BufReader::new(file))
+}
+
+pub fn read_manifest_from_string(string : &str) -> Result<Manifest, String> {
+    read_manifest_from_reader(&mut BufReader::new(string.as_bytes()))
+}
+
+/// Reads a manifest written in TOML, as a `menu_music = "<file>"` key and arrays of tables:
+///
+/// - `[[shader]]` with `name`, `vertex` and `fragment`
+/// - `[[texture]]` with `name`, `file` and optionally `alpha = true|false`,
+///   `filter = "linear"|"nearest"` and `wrap = "repeat"|"clamp"|"mirror"`
+/// - `[[sound]]` and `[[font]]` with `name` and `file`
+/// - `[[level]]` with `file` and optionally `music`, in the order they are played
+///
+/// Only the part of TOML this needs is understood - basic strings, booleans, comments and
+/// arrays of tables.
+pub fn read_manifest_from_reader<R : BufRead>(reader : &mut R) -> Result<Manifest, String> {
+    let tables = read_tables(reader)
+        .map_err(|e| format!("Invalid manifest format - {}", e))?;
+
+    let mut manifest = Manifest {
+        shaders: Vec::new(),
+        textures: Vec::new(),
+        sounds: Vec::new(),
+        fonts: Vec::new(),
+        levels: LevelList::new(),
+    };
+
+    for table in tables.into_iter() {
+        add_manifest_entry(&mut manifest, table)
+            .map_err(|e| format!("Invalid manifest format - {}", e))?;
+    }
+
+    if manifest.levels.levels.is_empty() {
+        return Err(format!("Invalid manifest format - no levels listed"));
+    }
+
+    Ok(manifest)
+}
+
+fn add_manifest_entry(manifest: &mut Manifest, mut table: Table) -> Result<(), String> {
+    match table.kind.as_str() {
+        "" => manifest.levels.menu_music = table.string("menu_music")?,
+        "shader" => manifest.shaders.push(ShaderEntry {
+            name: table.required_string("name")?,
+            vertex_file: table.required_string("vertex")?,
+            fragment_file: table.required_string("fragment")?,
+        }),
+        "texture" => {
+            let texture = read_texture_entry(&mut table)?;
+            manifest.textures.push(texture);
+        }
+        "sound" | "font" => {
+            let entry = FileEntry {
+                name: table.required_string("name")?,
+                file: table.required_string("file")?,
+            };
+            if table.kind == "sound" {
+                manifest.sounds.push(entry);
+            } else {
+                manifest.fonts.push(entry);
+            }
+        }
+        "level" => manifest.levels.levels.push(LevelEntry {
+            file: table.required_string("file")?,
+            music: table.string("music")?,
+        }),
+        kind => return Err(format!("unknown entry [[{}]] at line {}", kind, table.line)),
+    }
+    table.finish()
+}
+
+fn read_texture_entry(table: &mut Table) -> Result<TextureEntry, String> {
+    let line = table.line;
+    let filter = match table.string("filter")? {
+        None => None,
+        Some(ref filter) if filter == "linear" => Some(gl::LINEAR),
+        Some(ref filter) if filter == "nearest" => Some(gl::NEAREST),
+        Some(filter) => return Err(format!("unknown texture filter {} in the texture at line {}", filter, line)),
+    };
+    let wrap = match table.string("wrap")? {
+        None => None,
+        Some(ref wrap) if wrap == "repeat" => Some(gl::REPEAT),
+        Some(ref wrap) if wrap == "clamp" => Some(gl::CLAMP_TO_EDGE),
+        Some(ref wrap) if wrap == "mirror" => Some(gl::MIRRORED_REPEAT),
+        Some(wrap) => return Err(format!("unknown texture wrap {} in the texture at line {}", wrap, line)),
+    };
+
+    Ok(TextureEntry {
+        name: table.required_string("name")?,
+        file: table.required_string("file")?,
+        is_alpha: table.boolean("alpha")?.unwrap_or(false),
+        filter: filter,
+        wrap: wrap,
+    })
+}
+
+/// Splits the file into the keys before the first table, followed by every `[[kind]]` table.
+fn read_tables<R : BufRead>(reader : &mut R) -> Result<Vec<Table>, String> {
+    let mut tables = vec![Table::new("", 1)];
+
+    for (number, line) in (1..).zip(reader.lines()) {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("#") {
+            continue;
+        }
+
+        if line.starts_with("[[") {
+            let end = line.find("]]").ok_or(format!("unclosed table name at line {}", number))?;
+            let kind = line[2..end].trim();
+            if !is_bare_key(kind) || !is_end_of_line(&line[end + 2..]) {
+                return Err(format!("invalid table name at line {}", number));
+            }
+            tables.push(Table::new(kind, number));
+        } else if line.starts_with("[") {
+            return Err(format!("only arrays of tables, such as [[level]], are supported at line {}", number));
+        } else {
+            let mut parts = line.splitn(2, "=");
+            let key = parts.next().unwrap_or("").trim();
+            let rest = parts.next().ok_or(format!("expected key = value at line {}", number))?;
+            if !is_bare_key(key) {
+                return Err(format!("invalid key {} at line {}", key, number));
+            }
+
+            let (value, rest) = parse_value(rest.trim(), number)?;
+            if !is_end_of_line(rest) {
+                return Err(format!("unexpected {} after the value at line {}", rest.trim(), number));
+            }
+
+            let table = tables.last_mut().expect("there is always a table");
+            if table.values.insert(key.to_string(), (value, number)).is_some() {
+                return Err(format!("duplicate key {} at line {}", key, number));
+            }
+        }
+    }
+
+    Ok(tables)
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// True if nothing but whitespace and perhaps a comment is left on the line.
+fn is_end_of_line(rest: &str) -> bool {
+    let rest = rest.trim();
+    rest.is_empty() || rest.starts_with("#")
+}
+
+/// Reads the value at the start of `text`, returning it and whatever follows it.
+fn parse_value(text: &str, line: usize) -> Result<(Value, &str), String> {
+    for &(word, value) in [("true", true), ("false", false)].iter() {
+        if text.starts_with(word) {
+            return Ok((Value::BOOLEAN(value), &text[word.len()..]));
+        }
+    }
+
+    if !text.starts_with("\"") {
+        return Err(format!("expected a string or true or false at line {}", line));
+    }
+
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Ok((Value::STRING(value), &text[index + 1..])),
+            '\\' => match chars.next() {
+                Some((_, '"')) => value.push('"'),
+                Some((_, '\\')) => value.push('\\'),
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                _ => return Err(format!("unsupported escape in string at line {}", line)),
+            },
+            c => value.push(c),
+        }
+    }
+    Err(format!("unclosed string at line {}", line))
+}
@@ -1,7 +1,22 @@
+pub mod bindings_serialization;
+pub mod canvas;
+pub mod font_serialization;
 pub mod game_level;
 pub mod game_serialization;
+pub mod manifest_serialization;
+pub mod post_processor;
+pub mod power_up_serialization;
+pub mod random;
+pub mod renderer;
+pub mod replay_serialization;
+pub mod screenshot;
+pub mod settings_serialization;
 pub mod shader;
+pub mod software_renderer;
+pub mod sound;
 pub mod sprite_renderer;
 pub mod string_utils;
+pub mod text_renderer;
 pub mod texture;
 pub mod timer;
+pub mod viewport;
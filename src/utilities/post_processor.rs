@@ -0,0 +1,297 @@
+extern crate gl;
+extern crate nalgebra;
+
+use super::shader::Shader;
+use super::sprite_renderer::buffer_data;
+use super::texture::Texture;
+
+use std::cell::RefCell;
+use std::mem::size_of;
+use std::ptr::null;
+use std::rc::Rc;
+
+use gl::types::{GLfloat, GLint, GLuint};
+use nalgebra::base::Vector2;
+
+/// Number of samples used by the multisampled framebuffer.
+const SAMPLES: GLint = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    SHAKE,
+    CONFUSE,
+    CHAOS,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct EffectState {
+    enabled: bool,
+    remaining: Option<GLfloat>,   // seconds until the effect switches itself off
+}
+
+/// Renders the scene into an off-screen framebuffer and draws it to the screen through
+/// the post processing shader.
+///
+/// Scene drawing should be wrapped in `begin_render` and `end_render`, followed by a call to
+/// `render` to present the result. The framebuffers should match the viewport in pixels, so the
+/// scene is drawn at full resolution - `resize` them whenever it changes.
+pub struct PostProcessor {
+    shader: Rc<RefCell<Shader>>,
+    framebuffers: Framebuffers,
+    vao: GLuint,
+    saved_viewport: [GLint; 4],
+    effects: [EffectState; 3],
+}
+
+fn effect_index(effect: Effect) -> usize {
+    match effect {
+        Effect::SHAKE => 0,
+        Effect::CONFUSE => 1,
+        Effect::CHAOS => 2,
+    }
+}
+
+/// The off-screen framebuffers the scene is drawn into, all of the same size in pixels.
+struct Framebuffers {
+    texture: Texture,
+    width: GLuint,
+    height: GLuint,
+    msfbo: GLuint,                // multisampled framebuffer the scene is drawn into
+    fbo: GLuint,                  // framebuffer the multisampled image is resolved into
+    rbo: GLuint,
+}
+
+impl Framebuffers {
+    fn new(width: GLuint, height: GLuint) -> Result<Self, String> {
+        let mut texture = Texture::new(width, height, null());
+        texture.with_wrap_S(gl::REPEAT)
+            .with_wrap_T(gl::REPEAT);
+
+        // anything already created is deleted again by `drop` if a later step fails
+        let mut framebuffers = Framebuffers {
+            texture: texture.build()?,
+            width: width,
+            height: height,
+            msfbo: 0,
+            fbo: 0,
+            rbo: 0,
+        };
+
+        unsafe {
+            gl::GenFramebuffers(1, &mut framebuffers.msfbo);
+            gl::GenFramebuffers(1, &mut framebuffers.fbo);
+            gl::GenRenderbuffers(1, &mut framebuffers.rbo);
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffers.msfbo);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, framebuffers.rbo);
+            gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, SAMPLES, gl::RGB, width as GLint, height as GLint);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::RENDERBUFFER, framebuffers.rbo);
+            if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                return Err(format!("| ERROR::POSTPROCESSOR: Failed to initialize multisampled framebuffer"));
+            }
+
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffers.fbo);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, framebuffers.texture.id(), 0);
+            if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                return Err(format!("| ERROR::POSTPROCESSOR: Failed to initialize framebuffer"));
+            }
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        Ok(framebuffers)
+    }
+}
+
+impl Drop for Framebuffers {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.msfbo);
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteRenderbuffers(1, &self.rbo);
+        }
+    }
+}
+
+impl PostProcessor {
+    /// Creates the post-processor with framebuffers of `width` by `height` pixels.
+    pub fn new(shader: &Rc<RefCell<Shader>>, width: GLuint, height: GLuint) -> Result<Self, String> {
+        let framebuffers = Framebuffers::new(width, height)?;
+        let vao = PostProcessor::init_render_data();
+
+        {
+            let offset = 1.0 / 300.0;
+            let offsets = [
+                Vector2::new(-offset,  offset),
+                Vector2::new(    0.0,  offset),
+                Vector2::new( offset,  offset),
+                Vector2::new(-offset,     0.0),
+                Vector2::new(    0.0,     0.0),
+                Vector2::new( offset,     0.0),
+                Vector2::new(-offset, -offset),
+                Vector2::new(    0.0, -offset),
+                Vector2::new( offset, -offset),
+            ];
+            let edge_kernel = [
+                -1, -1, -1,
+                -1,  8, -1,
+                -1, -1, -1,
+            ];
+            let blur_kernel = [
+                1.0 / 16.0, 2.0 / 16.0, 1.0 / 16.0,
+                2.0 / 16.0, 4.0 / 16.0, 2.0 / 16.0,
+                1.0 / 16.0, 2.0 / 16.0, 1.0 / 16.0,
+            ];
+
+            let mut _shader = shader.borrow_mut();
+            _shader.enable();
+            unsafe {
+                _shader.setInt("scene", 0);
+                _shader.setVector2fArray("offsets", &offsets);
+                _shader.setIntArray("edge_kernel", &edge_kernel);
+                _shader.setFloatArray("blur_kernel", &blur_kernel);
+            }
+        }
+
+        let disabled = EffectState { enabled: false, remaining: None };
+
+        Ok(PostProcessor {
+            shader: shader.clone(),
+            framebuffers: framebuffers,
+            vao: vao,
+            saved_viewport: [0; 4],
+            effects: [disabled; 3],
+        })
+    }
+
+    fn init_render_data() -> GLuint {
+        // full screen quad in normalized device coordinates - <vec2 position, vec2 texCoords>
+        let vertices: Vec<GLfloat> = vec![
+            -1.0, -1.0, 0.0, 0.0,
+             1.0, -1.0, 1.0, 0.0,
+             1.0,  1.0, 1.0, 1.0,
+
+            -1.0, -1.0, 0.0, 0.0,
+             1.0,  1.0, 1.0, 1.0,
+            -1.0,  1.0, 0.0, 1.0,
+        ];
+
+        let mut vao = 0;
+        let mut vbo = 0;
+
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+
+            buffer_data(vbo, &vertices);
+
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 4, gl::FLOAT, gl::FALSE, (4 * size_of::<GLfloat>()) as GLint, null());
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+
+        vao
+    }
+
+    /// Recreates the framebuffers at `width` by `height` pixels, keeping the old ones if that fails.
+    pub fn resize(&mut self, width: GLuint, height: GLuint) -> Result<(), String> {
+        if (width, height) != (self.framebuffers.width, self.framebuffers.height) {
+            self.framebuffers = Framebuffers::new(width, height)?;
+        }
+        Ok(())
+    }
+
+    pub fn enable(&mut self, effect: Effect) {
+        self.effects[effect_index(effect)] = EffectState { enabled: true, remaining: None };
+    }
+
+    /// Enables `effect` for `duration` seconds, extending it if it is already running on a timer.
+    pub fn enable_for(&mut self, effect: Effect, duration: GLfloat) {
+        let state = &mut self.effects[effect_index(effect)];
+        if state.enabled && state.remaining.is_none() {
+            return;
+        }
+        let remaining = state.remaining.unwrap_or(0.0).max(duration);
+        *state = EffectState { enabled: true, remaining: Some(remaining) };
+    }
+
+    pub fn disable(&mut self, effect: Effect) {
+        self.effects[effect_index(effect)] = EffectState { enabled: false, remaining: None };
+    }
+
+    pub fn is_enabled(&self, effect: Effect) -> bool {
+        self.effects[effect_index(effect)].enabled
+    }
+
+    /// Counts down timed effects, switching them off once they expire.
+    pub fn update(&mut self, dt: GLfloat) {
+        for state in self.effects.iter_mut() {
+            if let Some(remaining) = state.remaining {
+                let remaining = remaining - dt;
+                if remaining <= 0.0 {
+                    *state = EffectState { enabled: false, remaining: None };
+                } else {
+                    state.remaining = Some(remaining);
+                }
+            }
+        }
+    }
+
+    /// Redirects all subsequent drawing into the off-screen framebuffer.
+    pub fn begin_render(&mut self) {
+        unsafe {
+            gl::GetIntegerv(gl::VIEWPORT, self.saved_viewport.as_mut_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffers.msfbo);
+            gl::Viewport(0, 0, self.framebuffers.width as GLint, self.framebuffers.height as GLint);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+    }
+
+    /// Resolves the multisampled scene into the texture and restores the default framebuffer.
+    pub fn end_render(&mut self) {
+        let (width, height) = (self.framebuffers.width as GLint, self.framebuffers.height as GLint);
+        let viewport = self.saved_viewport;
+
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.framebuffers.msfbo);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.framebuffers.fbo);
+            gl::BlitFramebuffer(0, 0, width, height, 0, 0, width, height, gl::COLOR_BUFFER_BIT, gl::NEAREST);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        }
+    }
+
+    /// Draws the resolved scene to the current framebuffer with the enabled effects applied.
+    pub fn render(&mut self, time: GLfloat) {
+        {
+            let mut _shader = self.shader.borrow_mut();
+            _shader.enable();
+            unsafe {
+                _shader.setFloat("time", time);
+                _shader.setInt("confuse", self.is_enabled(Effect::CONFUSE) as GLint);
+                _shader.setInt("chaos", self.is_enabled(Effect::CHAOS) as GLint);
+                _shader.setInt("shake", self.is_enabled(Effect::SHAKE) as GLint);
+            }
+        }
+
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            self.framebuffers.texture.bind();
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, 6);
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Drop for PostProcessor {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
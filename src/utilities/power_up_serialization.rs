@@ -0,0 +1,98 @@
+extern crate gl;
+extern crate nalgebra;
+
+use objects::power_up::{PowerUpDefinition, PowerUpKind};
+
+use std::fs::File;
+use std::path::Path;
+use std::io::{BufRead, BufReader};
+
+use gl::types::GLfloat;
+use nalgebra::base::Vector3;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_correct_format() {
+        let definitions = read_power_ups_from_string("# comment\n\nspeed 0.5 10.0 powerup 0.5 0.5 1.0\nchaos 0.1 15 powerup 1 0 0\n").expect("Should work");
+        assert_eq!(definitions.len(), 2);
+        assert_eq!(definitions[0].kind, PowerUpKind::SPEED);
+        assert_eq!(definitions[1].duration, 15.0);
+        assert_eq!(definitions[1].texture, "powerup");
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_unknown_power_ups() {
+        read_power_ups_from_string("sped 0.5 10.0 powerup 0.5 0.5 1.0\n").expect("Should work");
+    }
+
+    #[test]
+    fn counts_lines_from_one() {
+        let e = read_power_ups_from_string("# comment\nsped 0.5 10.0 powerup 0.5 0.5 1.0\n").err().expect("Should work");
+        assert!(e.ends_with("at line 2"), "{}", e);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_chances_out_of_range() {
+        read_power_ups_from_string("speed 1.5 10.0 powerup 0.5 0.5 1.0\n").expect("Should work");
+    }
+}
+
+pub fn read_power_ups_from_file<T : AsRef<Path>>(filename: &T) -> Result<Vec<PowerUpDefinition>, String> {
+    let file = File::open(filename).map_err(|e| e.to_string())?;
+    read_power_ups_from_reader(&mut BufReader::new(file))
+}
+
+pub fn read_power_ups_from_string(string : &str) -> Result<Vec<PowerUpDefinition>, String> {
+    read_power_ups_from_reader(&mut BufReader::new(string.as_bytes()))
+}
+
+fn parse_number(value: &str, line: usize) -> Result<GLfloat, String> {
+    value.parse::<GLfloat>()
+        .map_err(|_| format!("Invalid power-up format - {} is not a number at line {}", value, line))
+}
+
+/// Reads one power-up per line in the form `name chance duration texture red green blue`.
+///
+/// Blank lines and lines starting with `#` are ignored.
+pub fn read_power_ups_from_reader<R : BufRead>(reader : &mut R) -> Result<Vec<PowerUpDefinition>, String> {
+    let mut definitions = Vec::new();
+
+    for (number, line) in (1..).zip(reader.lines()) {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("#") {
+            continue;
+        }
+
+        let fields = line.split_whitespace().collect::<Vec<&str>>();
+        if fields.len() != 7 {
+            return Err(format!("Invalid power-up format - expected 7 fields at line {}", number));
+        }
+
+        let kind = PowerUpKind::from_name(fields[0])
+            .ok_or(format!("Invalid power-up format - unknown power-up {} at line {}", fields[0], number))?;
+        let chance = parse_number(fields[1], number)?;
+        if chance < 0.0 || chance > 1.0 {
+            return Err(format!("Invalid power-up format - chance must be between 0 and 1 at line {}", number));
+        }
+
+        definitions.push(PowerUpDefinition {
+            kind: kind,
+            chance: chance,
+            duration: parse_number(fields[2], number)?,
+            texture: fields[3].to_string(),
+            color: Vector3::new(
+                parse_number(fields[4], number)?,
+                parse_number(fields[5], number)?,
+                parse_number(fields[6], number)?,
+            ),
+        });
+    }
+
+    Ok(definitions)
+}
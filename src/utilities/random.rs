@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn never_gets_stuck_on_zero() {
+        let mut random = Random::new(SEED_MIX);
+        assert!((0..4).any(|_| random.next_u32() != 0));
+    }
+}
+
+const SEED_MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Small xorshift pseudo random number generator.
+///
+/// Sequences are fully determined by the seed, so anything driven by it can be reproduced.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck on zero, so keep the state away from it
+        let state = match seed ^ SEED_MIX {
+            0 => SEED_MIX,
+            state => state,
+        };
+        Random {
+            state: state,
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 32) as u32
+    }
+
+    /// Returns a value in the range [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Returns a value in the range [min, max).
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
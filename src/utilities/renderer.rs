@@ -0,0 +1,61 @@
+extern crate nalgebra;
+
+use super::texture::Texture;
+
+use nalgebra::base::{Matrix4, Vector2, Vector3, Vector4};
+use nalgebra::geometry::{Rotation3, Transform3, Translation3};
+
+use gl::types::GLfloat;
+
+/// Something sprites can be drawn with - OpenGL through `SpriteRenderer`, or the CPU through
+/// `SoftwareRenderer`.
+pub trait Renderer {
+    /// Draws only part of `texture`, as used for atlases such as bitmap fonts.
+    ///
+    /// `region` holds the normalized (u, v, width, height) of the part to draw, with v measured
+    /// from the top of the image.
+    fn draw_sprite_region(
+        &mut self,
+        texture: &Texture,
+        position: &Vector2<GLfloat>,
+        size: &Vector2<GLfloat>,
+        rotate: GLfloat,
+        color: &Vector3<GLfloat>,
+        region: &Vector4<GLfloat>
+    );
+
+    fn draw_sprite_transformed(
+        &mut self,
+        texture: &Texture,
+        position: &Vector2<GLfloat>,
+        size: &Vector2<GLfloat>,
+        rotate: GLfloat,
+        color: &Vector3<GLfloat>
+    ) {
+        self.draw_sprite_region(texture, position, size, rotate, color, &Vector4::new(0.0, 0.0, 1.0, 1.0));
+    }
+}
+
+/// Matrix placing the unit quad at `position` with `size`, rotated by `rotate` around its centre.
+pub fn sprite_model(position: &Vector2<GLfloat>, size: &Vector2<GLfloat>, rotate: GLfloat) -> Matrix4<GLfloat> {
+    let position_vector = Translation3::from_vector(Vector3::new(position.x, position.y, 0.0));
+    let center_prime = Translation3::from_vector(Vector3::new(0.5 * size.x, 0.5 * size.y, 0.0));
+    let rotation = Rotation3::from_axis_angle(&Vector3::z_axis(), -rotate);
+    let center = Translation3::from_vector(Vector3::new(-0.5 * size.x, -0.5 * size.y, 0.0));
+    let scaling_matrix = Transform3::from_matrix_unchecked(Matrix4::new_nonuniform_scaling(
+        &Vector3::new(size.x, size.y, 1.0),
+    ));
+
+    let model =
+        // move to correct location on size
+        position_vector *
+
+        // rotate
+        center_prime *
+            rotation *
+            center *
+        // scale quad to correct size
+            scaling_matrix;
+
+    *model.matrix()
+}
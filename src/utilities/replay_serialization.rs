@@ -0,0 +1,269 @@
+extern crate nalgebra;
+extern crate sdl2;
+
+use systems::input_manager::{ControlScheme, InputFrame, INPUT_COUNT};
+
+use std::fs::File;
+use std::path::Path;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+
+use nalgebra::base::Vector2;
+use sdl2::keyboard::Keycode;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_written_frames() {
+        let mut input = InputFrame::new();
+        input.held[2] = true;
+        input.pressed[4] = true;
+        input.horizontal = -0.375;
+        input.mouse_motion = 0.1;
+        input.touch = Some(Vector2::new(0.25, 0.75));
+        input.pressed_keys = vec![Keycode::J, Keycode::Left];
+
+        let mut bytes = Vec::new();
+        write_replay_header(&mut bytes, 1234567890123, ControlScheme::TOUCH, Some(2)).expect("Should work");
+        write_replay_frame(&mut bytes, 1.0 / 60.0, &input).expect("Should work");
+        write_replay_frame(&mut bytes, 0.1, &InputFrame::new()).expect("Should work");
+
+        let replay = read_replay_from_string(&String::from_utf8(bytes).expect("Should work")).expect("Should work");
+        assert_eq!(replay.seed, 1234567890123);
+        assert_eq!(replay.scheme, ControlScheme::TOUCH);
+        assert_eq!(replay.level, Some(2));
+        assert_eq!(replay.frames.len(), 2);
+        // times must come back bit for bit for playback to match
+        assert_eq!(replay.frames[0].dt.to_bits(), (1.0f32 / 60.0).to_bits());
+        assert_eq!(replay.frames[0].input, input);
+        assert_eq!(replay.frames[1].input, InputFrame::new());
+    }
+
+    #[test]
+    fn reads_replays_started_from_the_menu() {
+        let replay = read_replay_from_string(&format!("{}\nseed 7\nscheme keyboard\nlevel menu\n", REPLAY_FORMAT)).expect("Should work");
+        assert_eq!(replay.level, None);
+        assert!(replay.frames.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_missing_headers() {
+        read_replay_from_string("frame 3c888889 0 0 0 0 0 0 - -\n").expect("Should work");
+    }
+}
+
+/// Version 2 replays hold one frame per fixed simulation step, with times in seconds, version 3
+/// records mouse and touch input in world units rather than fractions of the window, and version 4
+/// records the level the game started on.
+pub const REPLAY_FORMAT: &str = "breakout-replay 4";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayFrame {
+    pub dt: f32,
+    pub input: InputFrame,
+}
+
+/// A recorded game - replaying the frames from the same seed, control scheme and starting level
+/// reproduces it exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub scheme: ControlScheme,
+    pub level: Option<usize>,       // level the recording started playing, or `None` for the menu
+    pub frames: Vec<ReplayFrame>,
+}
+
+pub fn read_replay_from_file<T : AsRef<Path>>(filename: &T) -> Result<Replay, String> {
+    let file = File::open(filename).map_err(|e| e.to_string())?;
+    read_replay_from_reader(&mut BufReader::new(file))
+}
+
+pub fn read_replay_from_string(string : &str) -> Result<Replay, String> {
+    read_replay_from_reader(&mut BufReader::new(string.as_bytes()))
+}
+
+fn parse_hex(value: &str, line: usize) -> Result<u32, String> {
+    u32::from_str_radix(value, 16)
+        .map_err(|_| format!("Invalid replay format - {} is not a hex number at line {}", value, line))
+}
+
+fn parse_float(value: &str, line: usize) -> Result<f32, String> {
+    Ok(f32::from_bits(parse_hex(value, line)?))
+}
+
+fn parse_flags(value: &str, line: usize) -> Result<[bool; INPUT_COUNT], String> {
+    let bits = parse_hex(value, line)?;
+    let mut flags = [false; INPUT_COUNT];
+    for i in 0..INPUT_COUNT {
+        flags[i] = bits & (1 << i) != 0;
+    }
+    Ok(flags)
+}
+
+fn parse_frame(fields: &[&str], line: usize) -> Result<ReplayFrame, String> {
+    if fields.len() != 10 {
+        return Err(format!("Invalid replay format - expected 10 fields at line {}", line));
+    }
+
+    let touch = match fields[8] {
+        "-" => None,
+        touch => {
+            let parts = touch.split(",").collect::<Vec<&str>>();
+            if parts.len() != 2 {
+                return Err(format!("Invalid replay format - bad touch position at line {}", line));
+            }
+            Some(Vector2::new(parse_float(parts[0], line)?, parse_float(parts[1], line)?))
+        }
+    };
+
+    let mut pressed_keys = Vec::new();
+    if fields[9] != "-" {
+        for key in fields[9].split(",") {
+            let code = key.parse::<i32>()
+                .map_err(|_| format!("Invalid replay format - {} is not a key code at line {}", key, line))?;
+            pressed_keys.push(Keycode::from_i32(code)
+                .ok_or(format!("Invalid replay format - unknown key code {} at line {}", code, line))?);
+        }
+    }
+
+    Ok(ReplayFrame {
+        dt: parse_float(fields[1], line)?,
+        input: InputFrame {
+            held: parse_flags(fields[2], line)?,
+            pressed: parse_flags(fields[3], line)?,
+            released: parse_flags(fields[4], line)?,
+            horizontal: parse_float(fields[5], line)?,
+            mouse_motion: parse_float(fields[6], line)?,
+            pointer_pressed: fields[7] == "1",
+            touch: touch,
+            pressed_keys: pressed_keys,
+        },
+    })
+}
+
+/// Reads a replay made of a format line, `seed`, `scheme` and `level` lines and then one `frame`
+/// line per frame.
+pub fn read_replay_from_reader<R : BufRead>(reader : &mut R) -> Result<Replay, String> {
+    let mut lines = reader.lines().enumerate();
+
+    let mut next_line = |name: &str| -> Result<(usize, String), String> {
+        let (i, line) = lines.next().ok_or(format!("Invalid replay format - missing {}", name))?;
+        Ok((i, line.map_err(|e| e.to_string())?))
+    };
+
+    if next_line("format")?.1.trim() != REPLAY_FORMAT {
+        return Err(format!("Invalid replay format - expected {} at line 0", REPLAY_FORMAT));
+    }
+
+    let (i, line) = next_line("seed")?;
+    let seed = match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        &["seed", seed] => seed.parse::<u64>()
+            .map_err(|_| format!("Invalid replay format - {} is not a seed at line {}", seed, i))?,
+        _ => return Err(format!("Invalid replay format - expected seed at line {}", i)),
+    };
+
+    let (i, line) = next_line("scheme")?;
+    let scheme = match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        &["scheme", scheme] => ControlScheme::from_name(scheme)
+            .ok_or(format!("Invalid replay format - unknown control scheme {} at line {}", scheme, i))?,
+        _ => return Err(format!("Invalid replay format - expected scheme at line {}", i)),
+    };
+
+    let (i, line) = next_line("level")?;
+    let level = match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        &["level", "menu"] => None,
+        // levels are numbered from 1, as in the settings file
+        &["level", level] => match level.parse::<usize>() {
+            Ok(level) if level > 0 => Some(level - 1),
+            _ => return Err(format!("Invalid replay format - {} is not a level at line {}", level, i)),
+        },
+        _ => return Err(format!("Invalid replay format - expected level at line {}", i)),
+    };
+
+    let mut frames = Vec::new();
+    loop {
+        let (i, line) = match next_line("frame") {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let fields = line.split_whitespace().collect::<Vec<&str>>();
+        if fields.is_empty() {
+            continue;
+        }
+        if fields[0] != "frame" {
+            return Err(format!("Invalid replay format - expected frame at line {}", i));
+        }
+        frames.push(parse_frame(&fields, i)?);
+    }
+
+    Ok(Replay {
+        seed: seed,
+        scheme: scheme,
+        level: level,
+        frames: frames,
+    })
+}
+
+pub fn write_replay_header<W : Write>(writer: &mut W, seed: u64, scheme: ControlScheme, level: Option<usize>) -> Result<(), String> {
+    let level = level
+        .map(|level| (level + 1).to_string())
+        .unwrap_or("menu".to_string());
+
+    writeln!(writer, "{}", REPLAY_FORMAT).map_err(|e| e.to_string())?;
+    writeln!(writer, "seed {}", seed).map_err(|e| e.to_string())?;
+    writeln!(writer, "scheme {}", scheme.name()).map_err(|e| e.to_string())?;
+    writeln!(writer, "level {}", level).map_err(|e| e.to_string())
+}
+
+fn flags_to_hex(flags: &[bool; INPUT_COUNT]) -> String {
+    let bits = flags.iter().enumerate()
+        .fold(0u32, |bits, (i, &flag)| if flag { bits | (1 << i) } else { bits });
+    format!("{:x}", bits)
+}
+
+/// Writes one frame, storing floats by their bit pattern so they are read back exactly.
+pub fn write_replay_frame<W : Write>(writer: &mut W, dt: f32, input: &InputFrame) -> Result<(), String> {
+    let touch = input.touch
+        .map(|touch| format!("{:08x},{:08x}", touch.x.to_bits(), touch.y.to_bits()))
+        .unwrap_or("-".to_string());
+    let keys = if input.pressed_keys.is_empty() {
+        "-".to_string()
+    } else {
+        input.pressed_keys.iter()
+            .map(|&keycode| (keycode as i32).to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    };
+
+    writeln!(writer, "frame {:08x} {} {} {} {:08x} {:08x} {} {} {}",
+        dt.to_bits(),
+        flags_to_hex(&input.held),
+        flags_to_hex(&input.pressed),
+        flags_to_hex(&input.released),
+        input.horizontal.to_bits(),
+        input.mouse_motion.to_bits(),
+        input.pointer_pressed as u8,
+        touch,
+        keys,
+    ).map_err(|e| e.to_string())
+}
+
+/// Writes frames to a replay file as they are played.
+pub struct ReplayRecorder {
+    writer: BufWriter<File>,
+}
+
+impl ReplayRecorder {
+    pub fn new<T : AsRef<Path>>(filename: &T, seed: u64, scheme: ControlScheme, level: Option<usize>) -> Result<Self, String> {
+        let file = File::create(filename).map_err(|e| e.to_string())?;
+        let mut writer = BufWriter::new(file);
+        write_replay_header(&mut writer, seed, scheme, level)?;
+        Ok(ReplayRecorder { writer: writer })
+    }
+
+    pub fn record(&mut self, dt: f32, input: &InputFrame) -> Result<(), String> {
+        write_replay_frame(&mut self.writer, dt, input)
+    }
+}
@@ -0,0 +1,198 @@
+extern crate gl;
+extern crate png;
+
+use utilities::software_renderer::SoftwareRenderer;
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::os::raw::c_void;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gl::types::{GLint, GLsizei};
+use png::HasParameters;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flips_rows_upside_down() {
+        let mut pixels = vec![
+            1, 1, 1, 1, 2, 2, 2, 2,
+            3, 3, 3, 3, 4, 4, 4, 4,
+            5, 5, 5, 5, 6, 6, 6, 6,
+        ];
+        flip_rows(&mut pixels, 2, 3);
+        assert_eq!(pixels, vec![
+            5, 5, 5, 5, 6, 6, 6, 6,
+            3, 3, 3, 3, 4, 4, 4, 4,
+            1, 1, 1, 1, 2, 2, 2, 2,
+        ]);
+    }
+
+    #[test]
+    fn reads_back_written_png() {
+        let screenshot = Screenshot::new(2, 1, vec![255, 0, 0, 255, 0, 128, 255, 64]).expect("Should work");
+
+        let mut bytes = Vec::new();
+        write_png_to_writer(&mut bytes, &screenshot).expect("Should work");
+
+        let (info, mut reader) = png::Decoder::new(&bytes[..]).read_info().expect("Should work");
+        let mut pixels = vec![0; info.buffer_size()];
+        reader.next_frame(&mut pixels).expect("Should work");
+        assert_eq!((info.width, info.height), (2, 1));
+        assert_eq!(pixels, screenshot.pixels());
+        assert_eq!(read_png_from_reader(&bytes[..]).expect("Should work").pixels(), screenshot.pixels());
+    }
+
+    #[test]
+    fn counts_pixels_outside_the_tolerance() {
+        let first = Screenshot::new(2, 1, vec![10, 10, 10, 255, 10, 10, 10, 255]).expect("Should work");
+        let second = Screenshot::new(2, 1, vec![12, 10, 10, 255, 10, 30, 10, 255]).expect("Should work");
+        assert_eq!(first.count_differences(&second, 4), Ok(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_mismatched_sizes() {
+        Screenshot::new(2, 2, vec![0; 4]).expect("Should work");
+    }
+}
+
+pub const SCREENSHOT_DIRECTORY: &str = "screenshots";
+
+/// An RGBA image of a rendered frame, top row first.
+pub struct Screenshot {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Screenshot {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, String> {
+        if pixels.len() != (width * height * 4) as usize {
+            return Err(format!("Expected {} bytes of pixels for a {}x{} screenshot, found {}",
+                width * height * 4, width, height, pixels.len()));
+        }
+        Ok(Screenshot { width: width, height: height, pixels: pixels })
+    }
+
+    /// Reads back the frame drawn so far with `glReadPixels`, so it must be called after
+    /// rendering and before the window is swapped.
+    pub fn from_framebuffer(width: u32, height: u32) -> Self {
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        unsafe {
+            // rows are tightly packed, whatever the width
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                0, 0,
+                width as GLsizei, height as GLsizei,
+                gl::RGBA, gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut c_void,
+            );
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 4 as GLint);
+        }
+
+        // OpenGL returns the bottom row first
+        flip_rows(&mut pixels, width, height);
+        Screenshot { width: width, height: height, pixels: pixels }
+    }
+
+    pub fn from_software(renderer: &SoftwareRenderer) -> Self {
+        Screenshot {
+            width: renderer.width(),
+            height: renderer.height(),
+            pixels: renderer.pixels().to_vec(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Counts the pixels with any channel more than `tolerance` away from the same pixel in `other`.
+    pub fn count_differences(&self, other: &Screenshot, tolerance: u8) -> Result<usize, String> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(format!("Can not compare a {}x{} image with a {}x{} one",
+                self.width, self.height, other.width, other.height));
+        }
+
+        Ok(self.pixels.chunks(4).zip(other.pixels.chunks(4))
+            .filter(|&(first, second)| first.iter().zip(second.iter())
+                .any(|(&a, &b)| (a as i16 - b as i16).abs() > tolerance as i16))
+            .count())
+    }
+}
+
+/// Reverses the order of the rows of an RGBA image in place.
+pub fn flip_rows(pixels: &mut [u8], width: u32, height: u32) {
+    let row = (width * 4) as usize;
+    let height = height as usize;
+    for y in 0..height / 2 {
+        let (top, bottom) = pixels.split_at_mut((height - 1 - y) * row);
+        top[y * row..(y + 1) * row].swap_with_slice(&mut bottom[..row]);
+    }
+}
+
+pub fn read_png_from_file<T : AsRef<Path>>(filename: &T) -> Result<Screenshot, String> {
+    let file = File::open(filename).map_err(|e| e.to_string())?;
+    read_png_from_reader(BufReader::new(file))
+}
+
+/// Reads an 8 bit RGB or RGBA PNG, such as a saved screenshot.
+pub fn read_png_from_reader<R : Read>(reader: R) -> Result<Screenshot, String> {
+    let (info, mut reader) = png::Decoder::new(reader).read_info().map_err(|e| e.to_string())?;
+    let mut data = vec![0; info.buffer_size()];
+    reader.next_frame(&mut data).map_err(|e| e.to_string())?;
+
+    let pixels = match (info.color_type, info.bit_depth) {
+        (png::ColorType::RGBA, png::BitDepth::Eight) => data,
+        (png::ColorType::RGB, png::BitDepth::Eight) => data.chunks(3)
+            .flat_map(|texel| vec![texel[0], texel[1], texel[2], 255])
+            .collect(),
+        (color_type, bit_depth) => return Err(format!("Unsupported PNG format {:?} at {:?} bits", color_type, bit_depth)),
+    };
+    Screenshot::new(info.width, info.height, pixels)
+}
+
+pub fn write_png_to_file<T : AsRef<Path>>(filename: &T, screenshot: &Screenshot) -> Result<(), String> {
+    let file = File::create(filename).map_err(|e| e.to_string())?;
+    write_png_to_writer(&mut BufWriter::new(file), screenshot)
+}
+
+pub fn write_png_to_writer<W : Write>(writer: &mut W, screenshot: &Screenshot) -> Result<(), String> {
+    let mut encoder = png::Encoder::new(writer, screenshot.width, screenshot.height);
+    encoder.set(png::ColorType::RGBA).set(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&screenshot.pixels).map_err(|e| e.to_string())
+}
+
+/// Writes `screenshot` to a new PNG in `directory`, named after the current time, and returns its path.
+pub fn save_screenshot<T : AsRef<Path>>(directory: &T, screenshot: &Screenshot) -> Result<PathBuf, String> {
+    let directory = directory.as_ref();
+    fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| e.to_string())?;
+    let stamp = time.as_secs() * 1000 + (time.subsec_nanos() / 1_000_000) as u64;
+
+    // never overwrite an earlier screenshot taken within the same millisecond
+    let mut path = directory.join(format!("screenshot-{}.png", stamp));
+    let mut count = 1;
+    while path.exists() {
+        path = directory.join(format!("screenshot-{}-{}.png", stamp, count));
+        count += 1;
+    }
+
+    write_png_to_file(&path, screenshot)?;
+    Ok(path)
+}
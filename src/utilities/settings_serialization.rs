@@ -0,0 +1,255 @@
+use utilities::canvas::{ScaleMode, VIRTUAL_WIDTH, VIRTUAL_HEIGHT};
+
+use std::fs::File;
+use std::path::Path;
+use std::io::{BufRead, BufReader, Write};
+
+use gl::types::GLfloat;
+use nalgebra::base::Vector3;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_correct_format() {
+        let (settings, errors) = read_settings_from_string("# comment\n\nresolution = 1280x720\nfullscreen = true\nlevel = 2\ngame = test\n").expect("Should work");
+        assert!(errors.is_empty());
+        assert_eq!((settings.width, settings.height), (1280, 720));
+        assert!(settings.fullscreen);
+        assert_eq!(settings.level, Some(1));
+        assert_eq!(settings.game, GameKind::TEST_GAME);
+        // unlisted settings keep their defaults
+        assert_eq!(settings.vsync, Settings::new().vsync);
+    }
+
+    #[test]
+    fn reads_back_written_settings() {
+        let mut settings = Settings::new();
+        settings.set("gl_version", "3.3").expect("Should work");
+        settings.set("clear_color", "0, 0.5, 1").expect("Should work");
+        settings.set("asset_directory", "/opt/breakout").expect("Should work");
+        settings.set("scale", "integer").expect("Should work");
+
+        let mut bytes = Vec::new();
+        write_settings_to_writer(&mut bytes, &settings).expect("Should work");
+        let string = String::from_utf8(bytes).expect("Should work");
+        assert_eq!(read_settings_from_string(&string).expect("Should work"), (settings, Vec::new()));
+    }
+
+    #[test]
+    fn skips_unknown_settings() {
+        let (settings, errors) = read_settings_from_string("colour = red\nfullscreen = true\n").expect("Should work");
+        assert_eq!(errors.len(), 1);
+        // the rest of the file still applies
+        assert!(settings.fullscreen);
+    }
+
+    #[test]
+    fn skips_invalid_values() {
+        let (settings, errors) = read_settings_from_string("vsync = false\nresolution = 1280\nno value here\n").expect("Should work");
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].ends_with("at line 2") && errors[1].ends_with("at line 3"), "{:?}", errors);
+        assert!(!settings.vsync);
+        assert_eq!((settings.width, settings.height), (Settings::new().width, Settings::new().height));
+    }
+}
+
+pub const SETTINGS_FILE: &str = "config/settings.cfg";
+
+/// Names of every setting, as used in the settings file and as `--name=value` command line flags.
+pub const SETTING_NAMES: [&str; 9] = [
+    "resolution",
+    "fullscreen",
+    "vsync",
+    "gl_version",
+    "clear_color",
+    "scale",
+    "asset_directory",
+    "level",
+    "game",
+];
+
+/// Which game the executable runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameKind {
+    GAME,           // breakout
+    TEST_GAME,      // a single triangle, for checking the OpenGL setup works
+}
+
+impl GameKind {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            GameKind::GAME => "breakout",
+            GameKind::TEST_GAME => "test",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "breakout" => Some(GameKind::GAME),
+            "test" => Some(GameKind::TEST_GAME),
+            _ => None,
+        }
+    }
+}
+
+/// How the executable sets up its window and which game it starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub width: u32,                 // initial size of the window
+    pub height: u32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub gl_version: (u8, u8),       // major and minor version of the OpenGL core context
+    pub clear_color: Vector3<GLfloat>,
+    pub scale: ScaleMode,
+    pub asset_directory: Option<String>,    // searched for assets before the usual places
+    pub level: Option<usize>,       // level to start playing straight away, rather than the menu
+    pub game: GameKind,
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Settings {
+            width: VIRTUAL_WIDTH as u32,
+            height: VIRTUAL_HEIGHT as u32,
+            fullscreen: false,
+            vsync: true,
+            gl_version: (4, 5),
+            clear_color: Vector3::new(0.3, 0.3, 0.5),
+            scale: ScaleMode::FRACTIONAL,
+            asset_directory: None,
+            level: None,
+            game: GameKind::GAME,
+        }
+    }
+
+    /// Changes the setting called `name` to the text `value`, as written in the settings file.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match name {
+            "resolution" => {
+                let (width, height) = parse_pair(value, "x")?;
+                if width == 0 || height == 0 {
+                    return Err(format!("resolution must not be empty, found {}", value));
+                }
+                self.width = width;
+                self.height = height;
+            }
+            "fullscreen" => self.fullscreen = parse_bool(value)?,
+            "vsync" => self.vsync = parse_bool(value)?,
+            "gl_version" => self.gl_version = parse_pair(value, ".")?,
+            "clear_color" => {
+                let channels = value.split(",")
+                    .map(|channel| channel.trim().parse::<GLfloat>().map_err(|e| format!("{} in colour {}", e, value)))
+                    .collect::<Result<Vec<GLfloat>, String>>()?;
+                if channels.len() != 3 {
+                    return Err(format!("expected a colour as red, green, blue, found {}", value));
+                }
+                self.clear_color = Vector3::new(channels[0], channels[1], channels[2]);
+            }
+            "scale" => self.scale = ScaleMode::from_name(value)
+                .ok_or(format!("unknown scale mode {}", value))?,
+            "asset_directory" => self.asset_directory = match value {
+                "" => None,
+                _ => Some(value.to_string()),
+            },
+            "level" => self.level = match value {
+                "menu" => None,
+                // levels are numbered from 1, as shown in game
+                _ => match value.parse::<usize>() {
+                    Ok(level) if level > 0 => Some(level - 1),
+                    _ => return Err(format!("expected menu or a level number, found {}", value)),
+                },
+            },
+            "game" => self.game = GameKind::from_name(value)
+                .ok_or(format!("unknown game {}", value))?,
+            _ => return Err(format!("unknown setting {}", name)),
+        }
+        Ok(())
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("expected true or false, found {}", value)),
+    }
+}
+
+fn parse_pair<T : ::std::str::FromStr>(value: &str, separator: &str) -> Result<(T, T), String> {
+    let mut parts = value.splitn(2, separator).map(|part| part.trim().parse::<T>());
+    match (parts.next(), parts.next()) {
+        (Some(Ok(first)), Some(Ok(second))) => Ok((first, second)),
+        _ => Err(format!("expected two numbers separated by {}, found {}", separator, value)),
+    }
+}
+
+pub fn read_settings_from_file<T : AsRef<Path>>(filename: &T) -> Result<(Settings, Vec<String>), String> {
+    let file = File::open(filename).map_err(|e| e.to_string())?;
+    read_settings_from_reader(&mut BufReader::new(file))
+}
+
+pub fn read_settings_from_string(string : &str) -> Result<(Settings, Vec<String>), String> {
+    read_settings_from_reader(&mut BufReader::new(string.as_bytes()))
+}
+
+/// Reads lines of the form `setting = value`, with the names in `SETTING_NAMES`.
+///
+/// Settings that are not listed keep their defaults. Blank lines and lines starting with `#` are
+/// ignored. A bad line is skipped rather than failing the whole file, and is described in the
+/// list returned alongside the settings.
+pub fn read_settings_from_reader<R : BufRead>(reader : &mut R) -> Result<(Settings, Vec<String>), String> {
+    let mut settings = Settings::new();
+    let mut errors = Vec::new();
+
+    for (number, line) in (1..).zip(reader.lines()) {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("#") {
+            continue;
+        }
+
+        let mut parts = line.splitn(2, "=");
+        let name = parts.next().unwrap_or("").trim();
+        let value = match parts.next() {
+            Some(value) => value,
+            None => {
+                errors.push(format!("Invalid settings format - expected setting = value at line {}", number));
+                continue;
+            }
+        };
+
+        if let Err(e) = settings.set(name, value) {
+            errors.push(format!("Invalid settings format - {} at line {}", e, number));
+        }
+    }
+
+    Ok((settings, errors))
+}
+
+pub fn write_settings_to_file<T : AsRef<Path>>(filename: &T, settings: &Settings) -> Result<(), String> {
+    let mut file = File::create(filename).map_err(|e| e.to_string())?;
+    write_settings_to_writer(&mut file, settings)
+}
+
+pub fn write_settings_to_writer<W : Write>(writer: &mut W, settings: &Settings) -> Result<(), String> {
+    let color = settings.clear_color;
+    let level = settings.level
+        .map(|level| (level + 1).to_string())
+        .unwrap_or("menu".to_string());
+
+    writeln!(writer, "# setting = value, each of which can be overridden with --setting=value").map_err(|e| e.to_string())?;
+    writeln!(writer, "resolution = {}x{}", settings.width, settings.height).map_err(|e| e.to_string())?;
+    writeln!(writer, "fullscreen = {}", settings.fullscreen).map_err(|e| e.to_string())?;
+    writeln!(writer, "vsync = {}", settings.vsync).map_err(|e| e.to_string())?;
+    writeln!(writer, "gl_version = {}.{}", settings.gl_version.0, settings.gl_version.1).map_err(|e| e.to_string())?;
+    writeln!(writer, "clear_color = {}, {}, {}", color.x, color.y, color.z).map_err(|e| e.to_string())?;
+    writeln!(writer, "scale = {}", settings.scale.name()).map_err(|e| e.to_string())?;
+    writeln!(writer, "asset_directory = {}", settings.asset_directory.as_ref().map_or("", |directory| directory.as_str())).map_err(|e| e.to_string())?;
+    writeln!(writer, "level = {}", level).map_err(|e| e.to_string())?;
+    writeln!(writer, "game = {}", settings.game.name()).map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -8,7 +8,7 @@ use std::ptr;
 use std::ptr::null;
 
 use gl::types::{GLfloat, GLint, GLsizei, GLuint};
-use nalgebra::base::{Matrix4, Vector2, Vector3};
+use nalgebra::base::{Matrix4, Vector2, Vector3, Vector4};
 
 pub struct Shader {
     id: GLuint,
@@ -177,6 +177,63 @@ impl Shader {
         );
     }
 
+    pub unsafe fn setVector4f(&mut self, name: &str, value: &Vector4<GLfloat>) {
+        if self.use_shader {
+            self.enable();
+        }
+
+        let name = str_to_glchar(name);
+
+        gl::Uniform4fv(
+            gl::GetUniformLocation(self.id, name),
+            1,
+            value.as_slice().as_ptr(),
+        );
+    }
+
+    pub unsafe fn setFloatArray(&mut self, name: &str, values: &[GLfloat]) {
+        if self.use_shader {
+            self.enable();
+        }
+
+        let name = str_to_glchar(name);
+
+        gl::Uniform1fv(
+            gl::GetUniformLocation(self.id, name),
+            values.len() as GLsizei,
+            values.as_ptr(),
+        );
+    }
+
+    pub unsafe fn setIntArray(&mut self, name: &str, values: &[GLint]) {
+        if self.use_shader {
+            self.enable();
+        }
+
+        let name = str_to_glchar(name);
+
+        gl::Uniform1iv(
+            gl::GetUniformLocation(self.id, name),
+            values.len() as GLsizei,
+            values.as_ptr(),
+        );
+    }
+
+    pub unsafe fn setVector2fArray(&mut self, name: &str, values: &[Vector2<GLfloat>]) {
+        if self.use_shader {
+            self.enable();
+        }
+
+        let name = str_to_glchar(name);
+        let flattened: Vec<GLfloat> = values.iter().flat_map(|value| vec![value.x, value.y]).collect();
+
+        gl::Uniform2fv(
+            gl::GetUniformLocation(self.id, name),
+            values.len() as GLsizei,
+            flattened.as_ptr(),
+        );
+    }
+
     pub unsafe fn setMatrix4(&mut self, name: &str, value: &Matrix4<GLfloat>) {
         if self.use_shader {
             self.enable();
@@ -0,0 +1,218 @@
+extern crate nalgebra;
+
+use super::renderer::{Renderer, sprite_model};
+use super::texture::Texture;
+
+use nalgebra::base::{Matrix4, Vector2, Vector3, Vector4};
+
+use gl::types::GLfloat;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn white() -> Texture {
+        Texture::from_pixels(1, 1, vec![255, 255, 255, 255]).expect("Should work")
+    }
+
+    fn pixel(renderer: &SoftwareRenderer, x: u32, y: u32) -> &[u8] {
+        let index = ((y * renderer.width() + x) * 4) as usize;
+        &renderer.pixels()[index..index + 4]
+    }
+
+    /// A renderer with a projection of one unit per pixel and y pointing up, as the game uses.
+    fn renderer(width: u32, height: u32) -> SoftwareRenderer {
+        let mut renderer = SoftwareRenderer::new(width, height);
+        renderer.set_projection(&Matrix4::new_orthographic(0.0, width as GLfloat, 0.0, height as GLfloat, -1.0, 1.0));
+        renderer
+    }
+
+    #[test]
+    fn fills_tinted_quads_with_y_up() {
+        let mut renderer = renderer(8, 8);
+        renderer.draw_sprite_transformed(&white(), &Vector2::new(2.0, 0.0), &Vector2::new(4.0, 2.0), 0.0, &Vector3::new(1.0, 0.0, 0.0));
+
+        // the bottom rows of the world are the last rows of the image
+        assert_eq!(pixel(&renderer, 2, 7), &[255, 0, 0, 255]);
+        assert_eq!(pixel(&renderer, 5, 6), &[255, 0, 0, 255]);
+        assert_eq!(pixel(&renderer, 1, 7), &[0, 0, 0, 0]);
+        assert_eq!(pixel(&renderer, 2, 5), &[0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn rotates_around_the_centre() {
+        let mut renderer = renderer(8, 8);
+        let quarter_turn = ::std::f32::consts::PI / 2.0;
+        renderer.draw_sprite_transformed(&white(), &Vector2::new(0.0, 3.0), &Vector2::new(8.0, 2.0), quarter_turn, &Vector3::new(1.0, 1.0, 1.0));
+
+        // a wide bar turned on its end becomes a tall one
+        assert_eq!(pixel(&renderer, 3, 0)[3], 255);
+        assert_eq!(pixel(&renderer, 4, 7)[3], 255);
+        assert_eq!(pixel(&renderer, 0, 4)[3], 0);
+    }
+
+    #[test]
+    fn samples_regions_from_the_top_of_the_image() {
+        // top half red, bottom half blue
+        let texture = Texture::from_pixels(1, 4, vec![
+            255, 0, 0, 255,
+            255, 0, 0, 255,
+            0, 0, 255, 255,
+            0, 0, 255, 255,
+        ]).expect("Should work");
+        let mut renderer = renderer(4, 4);
+        renderer.draw_sprite_region(&texture, &Vector2::new(0.0, 0.0), &Vector2::new(4.0, 4.0), 0.0,
+            &Vector3::new(1.0, 1.0, 1.0), &Vector4::new(0.0, 0.0, 1.0, 0.5));
+
+        // away from the edges, where filtering wraps around to the other side of the texture
+        assert_eq!(pixel(&renderer, 0, 1), &[255, 0, 0, 255]);
+        assert_eq!(pixel(&renderer, 3, 2), &[255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn blends_with_source_alpha() {
+        let texture = Texture::from_pixels(1, 1, vec![255, 255, 255, 128]).expect("Should work");
+        let mut renderer = renderer(2, 2);
+        renderer.clear(&Vector4::new(0.0, 0.0, 1.0, 1.0));
+        renderer.draw_sprite_transformed(&texture, &Vector2::new(0.0, 0.0), &Vector2::new(2.0, 2.0), 0.0, &Vector3::new(1.0, 1.0, 1.0));
+
+        assert_eq!(pixel(&renderer, 0, 0), &[128, 128, 255, 191]);
+    }
+
+    #[test]
+    fn culls_mirrored_quads() {
+        let mut renderer = renderer(4, 4);
+        renderer.draw_sprite_transformed(&white(), &Vector2::new(4.0, 0.0), &Vector2::new(-4.0, 4.0), 0.0, &Vector3::new(1.0, 1.0, 1.0));
+
+        assert!(renderer.pixels().iter().all(|&byte| byte == 0));
+    }
+}
+
+/// Draws sprites into an RGBA buffer on the CPU, for screenshots and image tests without a GPU.
+///
+/// Quads go through the same model and projection matrices as the OpenGL path, are sampled with
+/// the texture's own filtering and are alpha blended, so the results match closely enough for
+/// image diffing with a tolerance.
+pub struct SoftwareRenderer {
+    width: u32,
+    height: u32,
+    projection: Matrix4<GLfloat>,
+    pixels: Vec<u8>,        // RGBA, top row first
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        SoftwareRenderer {
+            width: width,
+            height: height,
+            projection: Matrix4::identity(),
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    /// Sets the matrix taking world positions to normalized device coordinates, as the `sprite`
+    /// shader's `projection` uniform does.
+    pub fn set_projection(&mut self, projection: &Matrix4<GLfloat>) {
+        self.projection = *projection;
+    }
+
+    pub fn clear(&mut self, color: &Vector4<GLfloat>) {
+        let color = [to_byte(color.x), to_byte(color.y), to_byte(color.z), to_byte(color.w)];
+        for pixel in self.pixels.chunks_mut(4) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Maps a point of the unit quad to pixel coordinates, with y pointing down the image.
+    fn to_pixel(&self, transform: &Matrix4<GLfloat>, x: GLfloat, y: GLfloat) -> Vector2<GLfloat> {
+        let clip = transform * Vector4::new(x, y, 0.0, 1.0);
+        Vector2::new(
+            (clip.x / clip.w + 1.0) * 0.5 * self.width as GLfloat,
+            (1.0 - clip.y / clip.w) * 0.5 * self.height as GLfloat,
+        )
+    }
+
+    fn blend(&mut self, x: u32, y: u32, source: &Vector4<GLfloat>) {
+        let index = ((y * self.width + x) * 4) as usize;
+        let pixel = &mut self.pixels[index..index + 4];
+        let alpha = source.w;
+
+        // glBlendFunc(SRC_ALPHA, ONE_MINUS_SRC_ALPHA), applied to every channel including alpha
+        for (channel, value) in pixel.iter_mut().zip([source.x, source.y, source.z, source.w].iter()) {
+            let destination = *channel as GLfloat / 255.0;
+            *channel = to_byte(value * alpha + destination * (1.0 - alpha));
+        }
+    }
+}
+
+fn to_byte(value: GLfloat) -> u8 {
+    (value.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+impl Renderer for SoftwareRenderer {
+    fn draw_sprite_region(
+        &mut self,
+        texture: &Texture,
+        position: &Vector2<GLfloat>,
+        size: &Vector2<GLfloat>,
+        rotate: GLfloat,
+        color: &Vector3<GLfloat>,
+        region: &Vector4<GLfloat>
+    ) {
+        let transform = self.projection * sprite_model(position, size, rotate);
+
+        // the projection is orthographic, so the quad stays a parallelogram on screen
+        let origin = self.to_pixel(&transform, 0.0, 0.0);
+        let across = self.to_pixel(&transform, 1.0, 0.0) - origin;
+        let up = self.to_pixel(&transform, 0.0, 1.0) - origin;
+
+        // with y flipped, quads facing the camera wind clockwise - the rest are culled as GL_CULL_FACE does
+        let determinant = across.x * up.y - across.y * up.x;
+        if determinant >= 0.0 {
+            return;
+        }
+
+        let corners = [origin, origin + across, origin + up, origin + across + up];
+        let min_x = corners.iter().map(|corner| corner.x).fold(GLfloat::INFINITY, GLfloat::min);
+        let max_x = corners.iter().map(|corner| corner.x).fold(GLfloat::NEG_INFINITY, GLfloat::max);
+        let min_y = corners.iter().map(|corner| corner.y).fold(GLfloat::INFINITY, GLfloat::min);
+        let max_y = corners.iter().map(|corner| corner.y).fold(GLfloat::NEG_INFINITY, GLfloat::max);
+
+        let start_x = min_x.floor().max(0.0) as u32;
+        let end_x = max_x.ceil().min(self.width as GLfloat).max(0.0) as u32;
+        let start_y = min_y.floor().max(0.0) as u32;
+        let end_y = max_y.ceil().min(self.height as GLfloat).max(0.0) as u32;
+
+        for y in start_y..end_y {
+            for x in start_x..end_x {
+                // pixels are covered when their centre lies inside the quad
+                let offset = Vector2::new(x as GLfloat + 0.5, y as GLfloat + 0.5) - origin;
+                let s = (offset.x * up.y - offset.y * up.x) / determinant;
+                let t = (across.x * offset.y - across.y * offset.x) / determinant;
+                if s < 0.0 || s >= 1.0 || t < 0.0 || t >= 1.0 {
+                    continue;
+                }
+
+                // matches the sprite vertex shader - the top of the quad shows the top of the region
+                let u = region.x + s * region.z;
+                let v = region.y + (1.0 - t) * region.w;
+                let texel = texture.sample(u, v);
+                let source = Vector4::new(color.x * texel.x, color.y * texel.y, color.z * texel.z, texel.w);
+
+                self.blend(x, y, &source);
+            }
+        }
+    }
+}
@@ -0,0 +1,450 @@
+extern crate lewton;
+
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use lewton::inside_ogg::OggStreamReader;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_written_wav() {
+        let mut bytes = Vec::new();
+        write_wav_header(&mut bytes, 22050, 2, 2).expect("Should work");
+        write_wav_samples(&mut bytes, &[0.0, 0.5, -0.5, 1.0]).expect("Should work");
+
+        let sound = read_wav_from_bytes(&bytes).expect("Should work");
+        assert_eq!(sound.sample_rate(), 22050);
+        assert_eq!(sound.channels(), 2);
+        assert_eq!(sound.frames(), 2);
+        assert!((sound.samples[1] - 0.5).abs() < 1e-3);
+        assert!((sound.samples[2] + 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn streams_wav_in_chunks() {
+        let path = ::std::env::temp_dir().join(format!("breakout_stream_test_{}.wav", ::std::process::id()));
+        {
+            let mut file = File::create(&path).expect("Should work");
+            write_wav_header(&mut file, 22050, 1, 5).expect("Should work");
+            write_wav_samples(&mut file, &[0.0, 0.25, 0.5, 0.75, 1.0]).expect("Should work");
+        }
+
+        let mut stream = open_stream_from_file(&path).expect("Should work");
+        let mut samples = Vec::new();
+        assert_eq!(stream.read(&mut samples, 3).expect("Should work"), 3);
+        assert_eq!(stream.read(&mut samples, 3).expect("Should work"), 2);
+        assert_eq!(stream.read(&mut samples, 3).expect("Should work"), 0);
+        assert!((samples[4] - 1.0).abs() < 1e-3);
+
+        stream.rewind().expect("Should work");
+        assert_eq!(stream.read(&mut samples, 8).expect("Should work"), 5);
+        ::std::fs::remove_file(&path).expect("Should work");
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_non_wav_data() {
+        read_wav_from_bytes(b"OggS not a wav file at all").expect("Should work");
+    }
+}
+
+/// Decoded audio, stored as interleaved samples in the range [-1, 1].
+///
+/// Sounds have either one or two channels - anything wider is mixed down to stereo on load.
+#[derive(Debug, Clone)]
+pub struct Sound {
+    samples: Vec<f32>,
+    channels: usize,
+    sample_rate: u32,
+}
+
+impl Sound {
+    pub fn new(samples: Vec<f32>, channels: usize, sample_rate: u32) -> Result<Self, String> {
+        if channels == 0 || sample_rate == 0 {
+            return Err(format!("Invalid sound - {} channels at {}Hz", channels, sample_rate));
+        }
+
+        let (samples, channels) = if channels > 2 {
+            // keep the first two channels, which are the front left and right in both WAV and Vorbis
+            let stereo = samples.chunks(channels)
+                .flat_map(|frame| vec![frame[0], frame[1]])
+                .collect();
+            (stereo, 2)
+        } else {
+            (samples, channels)
+        };
+
+        Ok(Sound {
+            samples: samples,
+            channels: channels,
+            sample_rate: sample_rate,
+        })
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    /// Returns the (left, right) samples of `frame`, or silence past the end of the sound.
+    pub fn frame(&self, frame: usize) -> (f32, f32) {
+        if frame >= self.frames() {
+            return (0.0, 0.0);
+        }
+        let index = frame * self.channels;
+        if self.channels == 1 {
+            (self.samples[index], self.samples[index])
+        } else {
+            (self.samples[index], self.samples[index + 1])
+        }
+    }
+}
+
+/// Loads a sound, choosing the decoder from the file extension.
+pub fn read_sound_from_file<T : AsRef<Path>>(filename: &T) -> Result<Sound, String> {
+    let path = filename.as_ref();
+    let extension = path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    match extension.as_ref().map(|extension| extension.as_str()) {
+        Some("wav") => {
+            let mut bytes = Vec::new();
+            File::open(path).map_err(|e| e.to_string())?
+                .read_to_end(&mut bytes)
+                .map_err(|e| e.to_string())?;
+            read_wav_from_bytes(&bytes)
+        }
+        Some("ogg") => read_ogg_from_file(&path),
+        _ => Err(format!("Unsupported sound format for {:?}", path)),
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, String> {
+    bytes.get(offset..offset + 2)
+        .map(|b| b[0] as u16 | (b[1] as u16) << 8)
+        .ok_or(format!("Invalid WAV file - unexpected end of data"))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes.get(offset..offset + 4)
+        .map(|b| b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24)
+        .ok_or(format!("Invalid WAV file - unexpected end of data"))
+}
+
+/// Description of the PCM data held in a WAV file.
+#[derive(Debug, Clone, Copy)]
+pub struct WavFormat {
+    pub channels: usize,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+    pub is_float: bool,
+}
+
+impl WavFormat {
+    pub fn bytes_per_sample(&self) -> usize {
+        self.bits_per_sample as usize / 8
+    }
+}
+
+/// Finds the format and the byte range of the sample data within a RIFF WAVE file.
+pub fn read_wav_layout<R : Read + Seek>(reader: &mut R) -> Result<(WavFormat, u64, u64), String> {
+    let length = reader.seek(SeekFrom::End(0)).map_err(|e| e.to_string())?;
+    reader.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+
+    let mut header = [0; 12];
+    reader.read_exact(&mut header).map_err(|_| format!("Invalid WAV file - missing RIFF header"))?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err(format!("Invalid WAV file - missing RIFF header"));
+    }
+
+    let mut format = None;
+    let mut offset = 12;
+
+    while offset + 8 <= length {
+        let mut chunk = [0; 8];
+        reader.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        reader.read_exact(&mut chunk).map_err(|e| e.to_string())?;
+        let size = read_u32(&chunk, 4)? as u64;
+        let body = offset + 8;
+
+        if &chunk[0..4] == b"fmt " {
+            // the extensible format block is the largest we understand
+            let mut bytes = vec![0; size.min(40) as usize];
+            reader.read_exact(&mut bytes).map_err(|_| format!("Invalid WAV file - unexpected end of data"))?;
+
+            let tag = read_u16(&bytes, 0)?;
+            let bits_per_sample = read_u16(&bytes, 14)?;
+            // 1 is integer PCM, 3 is IEEE float and 0xFFFE defers to the extension block
+            let is_float = tag == 3 || (tag == 0xFFFE && read_u16(&bytes, 24)? == 3);
+            if tag != 1 && tag != 3 && tag != 0xFFFE {
+                return Err(format!("Invalid WAV file - unsupported encoding {}", tag));
+            }
+            format = Some(WavFormat {
+                channels: read_u16(&bytes, 2)? as usize,
+                sample_rate: read_u32(&bytes, 4)?,
+                bits_per_sample: bits_per_sample,
+                is_float: is_float,
+            });
+        } else if &chunk[0..4] == b"data" {
+            let format = format.ok_or(format!("Invalid WAV file - data before fmt chunk"))?;
+            let end = (body + size).min(length);
+            return Ok((format, body, end));
+        }
+
+        // chunks are padded to an even number of bytes
+        offset = body + size + (size & 1);
+    }
+
+    Err(format!("Invalid WAV file - missing data chunk"))
+}
+
+/// Converts raw little-endian PCM samples to floats, appending them to `output`.
+pub fn decode_pcm(format: &WavFormat, data: &[u8], output: &mut Vec<f32>) -> Result<(), String> {
+    let width = format.bytes_per_sample();
+
+    for sample in data.chunks(width) {
+        if sample.len() < width {
+            break;
+        }
+        let value = match (format.bits_per_sample, format.is_float) {
+            (8, false) => (sample[0] as f32 - 128.0) / 128.0,
+            (16, false) => (sample[0] as u16 | (sample[1] as u16) << 8) as i16 as f32 / 32768.0,
+            (24, false) => {
+                let value = (sample[0] as u32) << 8 | (sample[1] as u32) << 16 | (sample[2] as u32) << 24;
+                (value as i32 >> 8) as f32 / 8388608.0
+            }
+            (32, false) => {
+                let value = sample[0] as u32 | (sample[1] as u32) << 8 | (sample[2] as u32) << 16 | (sample[3] as u32) << 24;
+                value as i32 as f32 / 2147483648.0
+            }
+            (32, true) => {
+                let value = sample[0] as u32 | (sample[1] as u32) << 8 | (sample[2] as u32) << 16 | (sample[3] as u32) << 24;
+                f32::from_bits(value)
+            }
+            (bits, _) => return Err(format!("Invalid WAV file - unsupported {} bit samples", bits)),
+        };
+        output.push(value);
+    }
+
+    Ok(())
+}
+
+pub fn read_wav_from_bytes(bytes: &[u8]) -> Result<Sound, String> {
+    let (format, start, end) = read_wav_layout(&mut Cursor::new(bytes))?;
+    let (start, end) = (start as usize, end as usize);
+    let mut samples = Vec::with_capacity((end - start) / format.bytes_per_sample().max(1));
+    decode_pcm(&format, &bytes[start..end], &mut samples)?;
+    Sound::new(samples, format.channels, format.sample_rate)
+}
+
+pub fn read_ogg_from_file<T : AsRef<Path>>(filename: &T) -> Result<Sound, String> {
+    let file = File::open(filename).map_err(|e| e.to_string())?;
+    let mut reader = OggStreamReader::new(BufReader::new(file))
+        .map_err(|e| format!("Invalid OGG file - {:?}", e))?;
+
+    let channels = reader.ident_hdr.audio_channels as usize;
+    let sample_rate = reader.ident_hdr.audio_sample_rate;
+    let mut samples = Vec::new();
+
+    while let Some(packet) = reader.read_dec_packet_itl().map_err(|e| format!("Invalid OGG file - {:?}", e))? {
+        samples.extend(packet.iter().map(|&sample| sample as f32 / 32768.0));
+    }
+
+    Sound::new(samples, channels, sample_rate)
+}
+
+/// Audio that is decoded from disk a piece at a time, for tracks too long to keep in memory.
+///
+/// Streams are handed over to the mixer, which may run on the audio thread, so they must be `Send`.
+pub trait SoundStream : Send {
+    fn channels(&self) -> usize;
+
+    fn sample_rate(&self) -> u32;
+
+    /// Appends up to `frames` frames of interleaved samples to `output`, returning the number read.
+    ///
+    /// Returns 0 once the end of the stream has been reached.
+    fn read(&mut self, output: &mut Vec<f32>, frames: usize) -> Result<usize, String>;
+
+    /// Starts the stream again from the beginning.
+    fn rewind(&mut self) -> Result<(), String>;
+}
+
+/// Opens a sound for streaming, choosing the decoder from the file extension.
+pub fn open_stream_from_file<T : AsRef<Path>>(filename: &T) -> Result<Box<SoundStream>, String> {
+    let path = filename.as_ref();
+    let extension = path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    match extension.as_ref().map(|extension| extension.as_str()) {
+        Some("wav") => Ok(Box::new(WavStream::new(&path)?)),
+        Some("ogg") => Ok(Box::new(OggStream::new(&path)?)),
+        _ => Err(format!("Unsupported sound format for {:?}", path)),
+    }
+}
+
+pub struct WavStream {
+    reader: BufReader<File>,
+    format: WavFormat,
+    start: u64,
+    end: u64,
+    position: u64,      // byte offset of the next sample within the file
+    bytes: Vec<u8>,
+}
+
+impl WavStream {
+    pub fn new<T : AsRef<Path>>(filename: &T) -> Result<Self, String> {
+        let mut reader = BufReader::new(File::open(filename).map_err(|e| e.to_string())?);
+        let (format, start, end) = read_wav_layout(&mut reader)?;
+        if format.channels == 0 || format.bytes_per_sample() == 0 {
+            return Err(format!("Invalid WAV file - empty sample format"));
+        }
+        reader.seek(SeekFrom::Start(start)).map_err(|e| e.to_string())?;
+
+        Ok(WavStream {
+            reader: reader,
+            format: format,
+            start: start,
+            end: end,
+            position: start,
+            bytes: Vec::new(),
+        })
+    }
+}
+
+impl SoundStream for WavStream {
+    fn channels(&self) -> usize {
+        self.format.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.format.sample_rate
+    }
+
+    fn read(&mut self, output: &mut Vec<f32>, frames: usize) -> Result<usize, String> {
+        let frame_size = (self.format.channels * self.format.bytes_per_sample()) as u64;
+        let available = (self.end - self.position) / frame_size;
+        let frames = (frames as u64).min(available);
+
+        self.bytes.resize((frames * frame_size) as usize, 0);
+        self.reader.read_exact(&mut self.bytes).map_err(|e| e.to_string())?;
+        self.position += frames * frame_size;
+
+        decode_pcm(&self.format, &self.bytes, output)?;
+        Ok(frames as usize)
+    }
+
+    fn rewind(&mut self) -> Result<(), String> {
+        self.reader.seek(SeekFrom::Start(self.start)).map_err(|e| e.to_string())?;
+        self.position = self.start;
+        Ok(())
+    }
+}
+
+pub struct OggStream {
+    reader: OggStreamReader<BufReader<File>>,
+    path: PathBuf,
+    pending: Vec<f32>,  // decoded samples left over from the last packet
+}
+
+impl OggStream {
+    pub fn new<T : AsRef<Path>>(filename: &T) -> Result<Self, String> {
+        let path = filename.as_ref().to_path_buf();
+        Ok(OggStream {
+            reader: OggStream::open(&path)?,
+            path: path,
+            pending: Vec::new(),
+        })
+    }
+
+    fn open(path: &Path) -> Result<OggStreamReader<BufReader<File>>, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        OggStreamReader::new(BufReader::new(file))
+            .map_err(|e| format!("Invalid OGG file - {:?}", e))
+    }
+}
+
+impl SoundStream for OggStream {
+    fn channels(&self) -> usize {
+        self.reader.ident_hdr.audio_channels as usize
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.reader.ident_hdr.audio_sample_rate
+    }
+
+    fn read(&mut self, output: &mut Vec<f32>, frames: usize) -> Result<usize, String> {
+        let channels = self.channels();
+
+        while self.pending.len() < frames * channels {
+            match self.reader.read_dec_packet_itl().map_err(|e| format!("Invalid OGG file - {:?}", e))? {
+                Some(packet) => self.pending.extend(packet.iter().map(|&sample| sample as f32 / 32768.0)),
+                None => break,
+            }
+        }
+
+        let count = (frames * channels).min(self.pending.len() / channels * channels);
+        output.extend(self.pending.drain(..count));
+        Ok(count / channels)
+    }
+
+    fn rewind(&mut self) -> Result<(), String> {
+        // reopening is simpler than seeking and only happens once per loop
+        self.reader = OggStream::open(&self.path)?;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+/// Writes a 16 bit PCM WAV header for `frames` frames of audio.
+pub fn write_wav_header<W : Write>(writer: &mut W, sample_rate: u32, channels: u16, frames: u32) -> Result<(), String> {
+    let block_align = channels as u32 * 2;
+    let data_size = frames * block_align;
+
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&u32_bytes(36 + data_size));
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&u32_bytes(16));
+    header.extend_from_slice(&u16_bytes(1));
+    header.extend_from_slice(&u16_bytes(channels));
+    header.extend_from_slice(&u32_bytes(sample_rate));
+    header.extend_from_slice(&u32_bytes(sample_rate * block_align));
+    header.extend_from_slice(&u16_bytes(block_align as u16));
+    header.extend_from_slice(&u16_bytes(16));
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&u32_bytes(data_size));
+
+    writer.write_all(&header).map_err(|e| e.to_string())
+}
+
+/// Writes float samples as clamped 16 bit PCM.
+pub fn write_wav_samples<W : Write>(writer: &mut W, samples: &[f32]) -> Result<(), String> {
+    let mut bytes = Vec::with_capacity(samples.len() * 2);
+    for &sample in samples {
+        let value = (sample.max(-1.0).min(1.0) * 32767.0) as i16;
+        bytes.extend_from_slice(&u16_bytes(value as u16));
+    }
+    writer.write_all(&bytes).map_err(|e| e.to_string())
+}
+
+fn u16_bytes(value: u16) -> [u8; 2] {
+    [value as u8, (value >> 8) as u8]
+}
+
+fn u32_bytes(value: u32) -> [u8; 4] {
+    [value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8]
+}
@@ -1,6 +1,7 @@
 extern crate gl;
 extern crate nalgebra;
 
+use super::renderer::{Renderer, sprite_model};
 use super::shader::{ Shader};
 use super::texture::Texture;
 
@@ -12,7 +13,6 @@ use std::ptr::null;
 use std::rc::Rc;
 
 use nalgebra::base::{Matrix4, Unit, Vector2, Vector3, Vector4};
-use nalgebra::geometry::{Affine3, Rotation3, Similarity3, Transform3, Translation3};
 
 use gl::types::{GLfloat, GLint, GLsizeiptr, GLuint};
 
@@ -94,21 +94,6 @@ impl SpriteRenderer {
         }
     }
 
-    pub fn draw_sprite(&mut self, texture: &Texture, position: &Vector2<GLfloat>){
-        self.draw_sprite_transformed_internal(texture, position, None, None, None);
-    }
-
-    pub fn draw_sprite_transformed(
-        &mut self,
-        texture: &Texture,
-        position: &Vector2<GLfloat>,
-        size: &Vector2<GLfloat>,
-        rotate: GLfloat,
-        color: &Vector3<GLfloat>
-    ) {
-        self.draw_sprite_transformed_internal(texture, position, Some(size), Some(rotate), Some(color));
-    }
-
     fn draw_sprite_transformed_internal(
         &mut self,
         texture: &Texture,
@@ -116,7 +101,10 @@ impl SpriteRenderer {
         size: Option<&Vector2<GLfloat>>,
         rotate: Option<GLfloat>,
         color: Option<&Vector3<GLfloat>>,
+        region: Option<&Vector4<GLfloat>>,
     ) {
+        let full_region = Vector4::new(0.0, 0.0, 1.0, 1.0);
+        let region = region.unwrap_or(&full_region);
         let rotate = rotate.unwrap_or(self.rotation);
         let color = color.unwrap_or(&self.color);
         let size = size.unwrap_or(&self.size);
@@ -127,34 +115,14 @@ impl SpriteRenderer {
         }
 
         // construct the positionining matrix for the texture
-
-        let position_vector = Translation3::from_vector(Vector3::new(position.x, position.y, 0.0));
-        let center_prime = Translation3::from_vector(Vector3::new(0.5 * size.x, 0.5 * size.y, 0.0));
-        let rotation = Rotation3::from_axis_angle(&Vector3::z_axis(), -rotate);
-        let center = Translation3::from_vector(Vector3::new(-0.5 * size.x, -0.5 * size.y, 0.0));
-        let scaling_matrix = Transform3::from_matrix_unchecked(Matrix4::new_nonuniform_scaling(
-            &Vector3::new(size.x, size.y, 1.0),
-        ));
-
-        let mut model =
-            // move to correct location on size
-            position_vector *
-
-            // rotate 
-            center_prime *
-                rotation *
-                center *
-            // scale quad to correct size 
-                scaling_matrix;
-
-
-        let mut model = model.matrix();
+        let model = sprite_model(position, size, rotate);
 
 
         unsafe {
             // load the position matrix and color vector into the shader
             self.shader.borrow_mut().setMatrix4("model", &model);
             self.shader.borrow_mut().setVector3f("spriteColor", &color);
+            self.shader.borrow_mut().setVector4f("textureRegion", &region);
 
             // this function will be using texture_0 entry for storing textures
             gl::ActiveTexture(gl::TEXTURE0);
@@ -169,3 +137,17 @@ impl SpriteRenderer {
         }
     }
 }
+
+impl Renderer for SpriteRenderer {
+    fn draw_sprite_region(
+        &mut self,
+        texture: &Texture,
+        position: &Vector2<GLfloat>,
+        size: &Vector2<GLfloat>,
+        rotate: GLfloat,
+        color: &Vector3<GLfloat>,
+        region: &Vector4<GLfloat>
+    ) {
+        self.draw_sprite_transformed_internal(texture, position, Some(size), Some(rotate), Some(color), Some(region));
+    }
+}
@@ -0,0 +1,271 @@
+extern crate gl;
+extern crate nalgebra;
+
+use super::font_serialization::FontMetrics;
+use super::renderer::Renderer;
+use super::texture::Texture;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gl::types::GLfloat;
+use nalgebra::base::{Vector2, Vector3, Vector4};
+
+/// A bitmap font - BMFont metrics plus one atlas texture per page.
+pub struct Font {
+    metrics: FontMetrics,
+    pages: Vec<Rc<RefCell<Texture>>>,
+}
+
+impl Font {
+    pub fn new(metrics: FontMetrics, pages: Vec<Rc<RefCell<Texture>>>) -> Self {
+        Font {
+            metrics: metrics,
+            pages: pages,
+        }
+    }
+
+    pub fn metrics(&self) -> &FontMetrics {
+        &self.metrics
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextAlignment {
+    LEFT,
+    CENTER,
+    RIGHT,
+}
+
+/// How a string should be laid out - built up with the `with_*` methods.
+#[derive(Debug, Clone)]
+pub struct TextStyle {
+    scale: GLfloat,
+    color: Vector3<GLfloat>,
+    alignment: TextAlignment,
+    wrap_width: Option<GLfloat>,   // maximum line width in world units, if any
+}
+
+impl TextStyle {
+    pub fn new() -> Self {
+        TextStyle {
+            scale: 1.0,
+            color: Vector3::new(1.0, 1.0, 1.0),
+            alignment: TextAlignment::LEFT,
+            wrap_width: None,
+        }
+    }
+
+    pub fn with_scale(&mut self, scale: GLfloat) -> &mut Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_color(&mut self, color: Vector3<GLfloat>) -> &mut Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_alignment(&mut self, alignment: TextAlignment) -> &mut Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn with_wrap_width(&mut self, wrap_width: GLfloat) -> &mut Self {
+        self.wrap_width = Some(wrap_width);
+        self
+    }
+}
+
+/// A single glyph placed in the world, ready to be drawn.
+#[derive(Debug, Clone)]
+pub struct GlyphQuad {
+    pub position: Vector2<GLfloat>,
+    pub size: Vector2<GLfloat>,
+    pub region: Vector4<GLfloat>,   // normalized (u, v, width, height) within the page
+    pub page: usize,
+}
+
+fn measure_line(metrics: &FontMetrics, line: &str) -> GLfloat {
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in line.chars() {
+        if let Some(glyph) = metrics.glyphs.get(&c) {
+            if let Some(previous) = previous {
+                width += metrics.kerning(previous, c);
+            }
+            width += glyph.x_advance;
+            previous = Some(c);
+        }
+    }
+    width
+}
+
+/// Splits `text` into lines, breaking on newlines and on spaces once a line would exceed `wrap_width`.
+///
+/// `wrap_width` is in unscaled font units. Words wider than the limit are kept whole on their own line.
+fn wrap_lines(metrics: &FontMetrics, text: &str, wrap_width: Option<GLfloat>) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.split('\n') {
+        let wrap_width = match wrap_width {
+            Some(wrap_width) => wrap_width,
+            None => {
+                lines.push(paragraph.to_string());
+                continue;
+            }
+        };
+
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            if line.is_empty() {
+                line.push_str(word);
+                continue;
+            }
+
+            let candidate = format!("{} {}", line, word);
+            if measure_line(metrics, &candidate) > wrap_width {
+                lines.push(line);
+                line = word.to_string();
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Lays out `text` with `position` as the top of the first line.
+///
+/// The x coordinate of `position` is the left edge, centre or right edge of each line depending on
+/// the style's alignment. The world y axis points up, so successive lines move down the screen.
+pub fn layout_text(metrics: &FontMetrics, text: &str, position: &Vector2<GLfloat>, style: &TextStyle) -> Vec<GlyphQuad> {
+    let scale = style.scale;
+    let wrap_width = style.wrap_width.map(|width| width / scale);
+    let mut quads = Vec::new();
+    let mut line_top = position.y;
+
+    for line in wrap_lines(metrics, text, wrap_width) {
+        let width = measure_line(metrics, &line) * scale;
+        let mut pen = match style.alignment {
+            TextAlignment::LEFT => position.x,
+            TextAlignment::CENTER => position.x - width / 2.0,
+            TextAlignment::RIGHT => position.x - width,
+        };
+
+        let mut previous = None;
+        for c in line.chars() {
+            let glyph = match metrics.glyphs.get(&c) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+
+            if let Some(previous) = previous {
+                pen += metrics.kerning(previous, c) * scale;
+            }
+            previous = Some(c);
+
+            if glyph.width > 0.0 && glyph.height > 0.0 {
+                let size = Vector2::new(glyph.width * scale, glyph.height * scale);
+                quads.push(GlyphQuad {
+                    position: Vector2::new(
+                        pen + glyph.x_offset * scale,
+                        line_top - glyph.y_offset * scale - size.y,
+                    ),
+                    size: size,
+                    region: Vector4::new(
+                        glyph.x / metrics.scale_width,
+                        glyph.y / metrics.scale_height,
+                        glyph.width / metrics.scale_width,
+                        glyph.height / metrics.scale_height,
+                    ),
+                    page: glyph.page,
+                });
+            }
+
+            pen += glyph.x_advance * scale;
+        }
+
+        line_top -= metrics.line_height * scale;
+    }
+
+    quads
+}
+
+pub struct TextRenderer {
+    font: Rc<RefCell<Font>>,
+}
+
+impl TextRenderer {
+    pub fn new(font: &Rc<RefCell<Font>>) -> Self {
+        TextRenderer {
+            font: font.clone(),
+        }
+    }
+
+    /// Returns the width and height `text` would occupy when drawn with `style`.
+    pub fn measure(&self, text: &str, style: &TextStyle) -> Vector2<GLfloat> {
+        let font = self.font.borrow();
+        let metrics = font.metrics();
+        let wrap_width = style.wrap_width.map(|width| width / style.scale);
+        let lines = wrap_lines(metrics, text, wrap_width);
+
+        let width = lines.iter()
+            .map(|line| measure_line(metrics, line))
+            .fold(0.0, GLfloat::max);
+
+        Vector2::new(width * style.scale, lines.len() as GLfloat * metrics.line_height * style.scale)
+    }
+
+    pub fn draw_text(&self, renderer: &mut Renderer, text: &str, position: &Vector2<GLfloat>, style: &TextStyle) {
+        let font = self.font.borrow();
+
+        for quad in layout_text(font.metrics(), text, position, style) {
+            if let Some(page) = font.pages.get(quad.page) {
+                renderer.draw_sprite_region(
+                    &page.borrow(),
+                    &quad.position,
+                    &quad.size,
+                    0.0,
+                    &style.color,
+                    &quad.region,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utilities::font_serialization::read_font_from_string;
+
+    static FONT: &'static str = "common lineHeight=10 base=8 scaleW=100 scaleH=100 pages=1\npage id=0 file=\"test.png\"\nchar id=32 x=0 y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=5 page=0\nchar id=97 x=10 y=20 width=8 height=8 xoffset=1 yoffset=2 xadvance=10 page=0\n";
+
+    #[test]
+    fn aligns_lines_around_position() {
+        let metrics = read_font_from_string(FONT).expect("Should work");
+        let mut style = TextStyle::new();
+        style.with_alignment(TextAlignment::RIGHT).with_scale(2.0);
+
+        let quads = layout_text(&metrics, "aa", &Vector2::new(100.0, 50.0), &style);
+        assert_eq!(quads.len(), 2);
+        // two glyphs of advance 10 at scale 2 end at the anchor
+        assert_eq!(quads[0].position.x, 100.0 - 40.0 + 2.0);
+        assert_eq!(quads[0].position.y, 50.0 - 4.0 - 16.0);
+        assert_eq!(quads[0].region, Vector4::new(0.1, 0.2, 0.08, 0.08));
+    }
+
+    #[test]
+    fn wraps_on_spaces() {
+        let metrics = read_font_from_string(FONT).expect("Should work");
+        let mut style = TextStyle::new();
+        style.with_wrap_width(30.0);
+
+        let quads = layout_text(&metrics, "aa aa a", &Vector2::new(0.0, 0.0), &style);
+        let rows: Vec<GLfloat> = quads.iter().map(|quad| quad.position.y).collect();
+        assert_eq!(rows, vec![-10.0, -10.0, -20.0, -20.0, -30.0]);
+    }
+}
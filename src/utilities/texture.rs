@@ -1,6 +1,42 @@
 extern crate gl;
-use gl::types::{GLint, GLuint};
+extern crate nalgebra;
+
+use gl::types::{GLfloat, GLint, GLuint};
+use nalgebra::base::Vector4;
 use std::os::raw::c_void;
+use std::slice::from_raw_parts;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_the_alpha_of_textures_stored_without_it() {
+        let data: [u8; 4] = [255, 0, 0, 0];
+        let mut texture = Texture::new(1, 1, data.as_ptr() as *const c_void);
+        texture.with_alpha(false)
+            .with_image_format(gl::RGBA);
+        let texture = texture.build_on_cpu().expect("Should work");
+
+        assert_eq!(texture.sample(0.5, 0.5), Vector4::new(1.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn mirrors_coordinates_past_the_edges() {
+        let data: [u8; 8] = [0, 0, 0, 255, 255, 255, 255, 255];
+        let mut texture = Texture::new(2, 1, data.as_ptr() as *const c_void);
+        texture.with_alpha(true)
+            .with_filter_max(gl::NEAREST)
+            .with_wrap_S(gl::MIRRORED_REPEAT);
+        let texture = texture.build_on_cpu().expect("Should work");
+
+        // black then white, then white then black again, on either side
+        assert_eq!(texture.sample(1.25, 0.5).x, 1.0);
+        assert_eq!(texture.sample(1.75, 0.5).x, 0.0);
+        assert_eq!(texture.sample(-0.25, 0.5).x, 0.0);
+        assert_eq!(texture.sample(-0.75, 0.5).x, 1.0);
+    }
+}
 
 /// Struct used to represent texture
 pub struct Texture {
@@ -13,6 +49,7 @@ pub struct Texture {
     wrap_T: GLuint,          // wrapping on T axis
     filter_min: GLuint,      // filtering mode if pixels < screen pixels
     filter_max: GLuint,      // filtering mode if pixels > screen pixels
+    pixels: Vec<u8>,         // RGBA copy of the image, top row first, for drawing on the CPU - empty unless asked for
 }
 
 pub struct TextureBuilder {
@@ -25,6 +62,7 @@ pub struct TextureBuilder {
     wrap_T: Option<GLuint>,          // wrapping on T axis
     filter_min: Option<GLuint>,      // filtering mode if pixels < screen pixels
     filter_max: Option<GLuint>,      // filtering mode if pixels > screen pixels
+    keep_pixels: bool,               // keep an RGBA copy of the data for drawing on the CPU
 }
 
 impl TextureBuilder {
@@ -39,6 +77,7 @@ impl TextureBuilder {
             wrap_T: None,
             filter_min: None,
             filter_max: None,
+            keep_pixels: false,
         }
     }
 
@@ -72,6 +111,12 @@ impl TextureBuilder {
         self
     }
 
+    /// Keeps a copy of the image alongside the OpenGL texture, so the software renderer can draw it.
+    pub fn with_cpu_copy(&mut self, keep_pixels: bool) -> &mut Self {
+        self.keep_pixels = keep_pixels;
+        self
+    }
+
     pub fn with_alpha(&mut self, alpha: bool) -> &mut Self {
         if alpha {
             self.internal_format = Some(gl::RGBA);
@@ -83,6 +128,30 @@ impl TextureBuilder {
         self
     }
 
+    /// Makes a texture that only lives on the CPU, for drawing with the software renderer without
+    /// a graphics context.
+    pub fn build_on_cpu(self) -> Result<Texture, String> {
+        let internal_format = self.internal_format.unwrap_or(gl::RGB);
+        let image_format = self.image_format.unwrap_or(gl::RGB);
+        let pixels = unsafe { rgba_pixels(self.width, self.height, self.data, internal_format, image_format) };
+        if pixels.is_empty() {
+            return Err(format!("No image data to copy for a {}x{} texture", self.width, self.height));
+        }
+
+        Ok(Texture {
+            id: 0,
+            width: self.width,
+            height: self.height,
+            internal_format: internal_format,
+            image_format: image_format,
+            wrap_S: self.wrap_S.unwrap_or(gl::REPEAT),
+            wrap_T: self.wrap_T.unwrap_or(gl::REPEAT),
+            filter_min: self.filter_min.unwrap_or(gl::LINEAR),
+            filter_max: self.filter_max.unwrap_or(gl::LINEAR),
+            pixels: pixels,
+        })
+    }
+
     pub fn build(self) -> Result<Texture, String> {
         let internal_format = self.internal_format.unwrap_or(gl::RGB);
         let image_format = self.image_format.unwrap_or(gl::RGB);
@@ -90,6 +159,11 @@ impl TextureBuilder {
         let wrap_T = self.wrap_T.unwrap_or(gl::REPEAT);
         let filter_min = self.filter_min.unwrap_or(gl::LINEAR);
         let filter_max = self.filter_max.unwrap_or(gl::LINEAR);
+        let pixels = if self.keep_pixels {
+            unsafe { rgba_pixels(self.width, self.height, self.data, internal_format, image_format) }
+        } else {
+            Vec::new()
+        };
 
         Texture::construct(
             self.width,
@@ -101,10 +175,34 @@ impl TextureBuilder {
             wrap_T,
             filter_min,
             filter_max,
+            pixels,
         )
     }
 }
 
+/// Copies image data laid out as `image_format` into tightly packed RGBA.
+///
+/// `data` must hold `width * height` texels of `image_format`. Textures without any data, such
+/// as render targets, get no copy. Like OpenGL, an `internal_format` without alpha reads as opaque.
+unsafe fn rgba_pixels(width: GLuint, height: GLuint, data: *const c_void, internal_format: GLuint, image_format: GLuint) -> Vec<u8> {
+    let channels = match image_format {
+        gl::RGBA => 4,
+        gl::RGB => 3,
+        _ => return Vec::new(),
+    };
+    if data.is_null() {
+        return Vec::new();
+    }
+
+    let data = from_raw_parts(data as *const u8, (width * height * channels) as usize);
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for texel in data.chunks(channels as usize) {
+        pixels.extend_from_slice(&texel[..3]);
+        pixels.push(if channels == 4 && internal_format == gl::RGBA { texel[3] } else { 255 });
+    }
+    pixels
+}
+
 impl Texture {
     pub fn new(width: GLuint, height: GLuint, data: *const c_void) -> TextureBuilder {
         TextureBuilder::new(width, height, data)
@@ -119,6 +217,7 @@ impl Texture {
         wrap_T: GLuint,
         filter_min: GLuint,
         filter_max: GLuint,
+        pixels: Vec<u8>,
     ) -> Result<Self, String> {
         let mut id = 0;
 
@@ -159,19 +258,108 @@ impl Texture {
             wrap_T: wrap_T,
             filter_min: filter_min,
             filter_max: filter_max,
+            pixels: pixels,
+        })
+    }
+
+    /// Creates a texture that only lives on the CPU, for drawing without a graphics context.
+    ///
+    /// `pixels` holds RGBA bytes, top row first.
+    pub fn from_pixels(width: GLuint, height: GLuint, pixels: Vec<u8>) -> Result<Self, String> {
+        if pixels.len() != (width * height * 4) as usize {
+            return Err(format!("Expected {} bytes of pixels for a {}x{} texture, found {}",
+                width * height * 4, width, height, pixels.len()));
+        }
+
+        Ok(Texture {
+            id: 0,
+            width: width,
+            height: height,
+            internal_format: gl::RGBA,
+            image_format: gl::RGBA,
+            wrap_S: gl::REPEAT,
+            wrap_T: gl::REPEAT,
+            filter_min: gl::LINEAR,
+            filter_max: gl::LINEAR,
+            pixels: pixels,
         })
     }
 
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn width(&self) -> GLuint {
+        self.width
+    }
+
+    pub fn height(&self) -> GLuint {
+        self.height
+    }
+
     pub unsafe fn bind(&self) {
         gl::BindTexture(gl::TEXTURE_2D, self.id);
     }
 
+    /// Reads the colour at normalized (u, v), with v measured from the top of the image, filtered
+    /// and wrapped the way OpenGL would sample it.
+    ///
+    /// Textures without a CPU copy read as opaque white.
+    pub fn sample(&self, u: GLfloat, v: GLfloat) -> Vector4<GLfloat> {
+        if self.pixels.is_empty() {
+            return Vector4::new(1.0, 1.0, 1.0, 1.0);
+        }
+
+        // texel centres sit half a texel in from the edges
+        let x = u * self.width as GLfloat - 0.5;
+        let y = v * self.height as GLfloat - 0.5;
+
+        if self.filter_max == gl::NEAREST {
+            return self.texel((x + 0.5).floor() as i64, (y + 0.5).floor() as i64);
+        }
+
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.texel(x0, y0) * (1.0 - fx) + self.texel(x0 + 1, y0) * fx;
+        let bottom = self.texel(x0, y0 + 1) * (1.0 - fx) + self.texel(x0 + 1, y0 + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Vector4<GLfloat> {
+        let x = wrap(x, self.width, self.wrap_S);
+        let y = wrap(y, self.height, self.wrap_T);
+        let index = (y * self.width as usize + x) * 4;
+        let texel = &self.pixels[index..index + 4];
+
+        Vector4::new(texel[0] as GLfloat, texel[1] as GLfloat, texel[2] as GLfloat, texel[3] as GLfloat) / 255.0
+    }
+
+}
+
+/// Brings a texel coordinate into `0..size`, repeating, mirroring or clamping to the edges as
+/// `mode` says.
+fn wrap(coordinate: i64, size: GLuint, mode: GLuint) -> usize {
+    let size = size as i64;
+    match mode {
+        gl::REPEAT => (((coordinate % size) + size) % size) as usize,
+        gl::MIRRORED_REPEAT => {
+            // every other repeat runs backwards
+            let coordinate = ((coordinate % (2 * size)) + 2 * size) % (2 * size);
+            if coordinate < size { coordinate as usize } else { (2 * size - 1 - coordinate) as usize }
+        }
+        _ => coordinate.max(0).min(size - 1) as usize,
+    }
 }
 
 impl Drop for Texture {
     fn drop(&mut self) {
-        unsafe {
-            gl::DeleteTextures(1, &self.id);
+        // textures made on the CPU never had a GL object
+        if self.id != 0 {
+            unsafe {
+                gl::DeleteTextures(1, &self.id);
+            }
         }
     }
 }
\ No newline at end of file
@@ -1,9 +1,8 @@
-use std::time::{Duration, Instant, SystemTime};
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::time::Instant;
 
+/// Measures time from when it was created, using a monotonic clock.
 pub struct Timer {
-    start_time: SystemTime
+    start_time: Instant
 }
 
 
@@ -11,19 +10,13 @@ impl Timer {
 
     pub fn new() -> Self {
         Timer {
-            start_time: SystemTime::now()
+            start_time: Instant::now()
         }
     }
 
-    pub fn get_time(&self) -> f32 {
-        let current_time = SystemTime::now();
-        let elapsed_time =
-            current_time
-                .duration_since(self.start_time)
-                .expect("| ERROR::TIMER: Could not get elapsed time");
-        (elapsed_time.as_secs() * 1000) as f32
-            + (elapsed_time.subsec_nanos() as f32 / 1_000_000 as f32)
+    /// Seconds elapsed since the timer was created.
+    pub fn get_time(&self) -> f64 {
+        let elapsed_time = self.start_time.elapsed();
+        elapsed_time.as_secs() as f64 + elapsed_time.subsec_nanos() as f64 / 1_000_000_000.0
     }
 }
-
-
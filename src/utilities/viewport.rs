@@ -0,0 +1,100 @@
+extern crate gl;
+extern crate nalgebra;
+
+use gl::types::{GLfloat, GLint, GLsizei};
+use nalgebra::base::Vector2;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_bars_to_the_sides_of_wide_windows() {
+        let viewport = Viewport::letterbox(1000, 300, 4.0 / 3.0);
+        assert_eq!((viewport.x, viewport.y, viewport.width, viewport.height), (300, 0, 400, 300));
+    }
+
+    #[test]
+    fn adds_bars_above_and_below_tall_windows() {
+        let viewport = Viewport::letterbox(400, 1000, 4.0 / 3.0);
+        assert_eq!((viewport.x, viewport.y, viewport.width, viewport.height), (0, 350, 400, 300));
+    }
+
+    #[test]
+    fn maps_window_positions_into_the_viewport() {
+        let viewport = Viewport::letterbox(400, 1000, 4.0 / 3.0);
+        // the top of the viewport is 350 pixels down the window
+        let point = viewport.window_to_viewport(&Vector2::new(0.5, 0.35));
+        assert!((point.x - 0.5).abs() < 1e-6 && point.y.abs() < 1e-6);
+    }
+}
+
+/// The area of the window the game is drawn to, in pixels from the bottom left as `glViewport`
+/// takes it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub window_width: u32,
+    pub window_height: u32,
+}
+
+impl Viewport {
+    /// Covers the whole window.
+    pub fn full(window_width: u32, window_height: u32) -> Self {
+        Viewport {
+            x: 0,
+            y: 0,
+            width: window_width,
+            height: window_height,
+            window_width: window_width,
+            window_height: window_height,
+        }
+    }
+
+    /// The largest area of the window with the given width to height ratio, centred so the rest
+    /// of the window is left as bars on either side or above and below.
+    pub fn letterbox(window_width: u32, window_height: u32, aspect: GLfloat) -> Self {
+        let window_width = window_width.max(1);
+        let window_height = window_height.max(1);
+
+        let (width, height) = if window_width as GLfloat / window_height as GLfloat > aspect {
+            ((window_height as GLfloat * aspect).round() as u32, window_height)
+        } else {
+            (window_width, (window_width as GLfloat / aspect).round() as u32)
+        };
+
+        Viewport::centered(window_width, window_height, width, height)
+    }
+
+    /// An area of the given size in the middle of the window.
+    pub fn centered(window_width: u32, window_height: u32, width: u32, height: u32) -> Self {
+        let window_width = window_width.max(1);
+        let window_height = window_height.max(1);
+
+        Viewport {
+            x: (window_width as i32 - width as i32) / 2,
+            y: (window_height as i32 - height as i32) / 2,
+            width: width.max(1),
+            height: height.max(1),
+            window_width: window_width,
+            window_height: window_height,
+        }
+    }
+
+    /// Converts a position normalized to the whole window, with y pointing down, to one normalized
+    /// to the viewport. Positions over the bars fall outside [0, 1].
+    pub fn window_to_viewport(&self, point: &Vector2<GLfloat>) -> Vector2<GLfloat> {
+        let x = point.x * self.window_width as GLfloat - self.x as GLfloat;
+        // `y` counts up from the bottom of the window
+        let top = self.window_height as GLfloat - (self.y as GLfloat + self.height as GLfloat);
+        let y = point.y * self.window_height as GLfloat - top;
+        Vector2::new(x / self.width as GLfloat, y / self.height as GLfloat)
+    }
+
+    pub unsafe fn apply(&self) {
+        gl::Viewport(self.x as GLint, self.y as GLint, self.width as GLsizei, self.height as GLsizei);
+    }
+}